@@ -0,0 +1,278 @@
+//! OAuth access tokens for backends authenticated with bearer tokens, such as Vertex AI.
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use futures_util::future::BoxFuture;
+use reqwest::Client as HttpClient;
+use rsa::pkcs1v15::SigningKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::sha2::Sha256;
+use rsa::signature::{SignatureEncoding, Signer};
+use rsa::RsaPrivateKey;
+use serde::Deserialize;
+use serde_json::json;
+use std::fmt::{self, Debug};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// The scope requested for service-account and metadata-server tokens.
+pub const CLOUD_PLATFORM_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+
+const METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+
+/// Tokens are refreshed this long before they expire.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// An OAuth access token.
+#[derive(Clone)]
+pub struct AccessToken {
+    pub token: String,
+    /// When the token stops being valid, `None` if it never expires.
+    pub expires_at: Option<Instant>,
+}
+
+impl Debug for AccessToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessToken")
+            .field("token", &"<redacted>")
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+impl AccessToken {
+    fn is_fresh(&self) -> bool {
+        self.expires_at
+            .is_none_or(|expires_at| Instant::now() + EXPIRY_MARGIN < expires_at)
+    }
+}
+
+/// A source of access tokens, see `CBuilder::credentials`.
+///
+/// Tokens are cached by the client and only fetched again shortly before they expire.
+pub trait TokenProvider: Debug + Send + Sync {
+    fn fetch<'a>(&'a self, http: &'a HttpClient) -> BoxFuture<'a, Result<AccessToken>>;
+}
+
+/// A token obtained elsewhere, e.g. with `gcloud auth print-access-token`.
+#[derive(Clone)]
+pub struct StaticToken(pub String);
+
+impl Debug for StaticToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StaticToken(<redacted>)")
+    }
+}
+
+impl TokenProvider for StaticToken {
+    fn fetch<'a>(&'a self, _http: &'a HttpClient) -> BoxFuture<'a, Result<AccessToken>> {
+        Box::pin(async move {
+            Ok(AccessToken {
+                token: self.0.clone(),
+                expires_at: None,
+            })
+        })
+    }
+}
+
+/// The token endpoint answer shared by the metadata server and the OAuth token URI.
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+impl TokenResponse {
+    fn into_token(self) -> AccessToken {
+        AccessToken {
+            token: self.access_token,
+            expires_at: self
+                .expires_in
+                .map(|secs| Instant::now() + Duration::from_secs(secs)),
+        }
+    }
+}
+
+async fn token_response(res: reqwest::Response) -> Result<AccessToken> {
+    let status = res.status();
+    if !status.is_success() {
+        let body = res.text().await.unwrap_or_default();
+        return Err(anyhow!(
+            "Failed to fetch an access token (status {}): {}",
+            status,
+            body
+        ));
+    }
+    Ok(res.json::<TokenResponse>().await?.into_token())
+}
+
+/// The default service account of the GCE, GKE or Cloud Run instance running the code.
+#[derive(Debug, Clone)]
+pub struct MetadataServer {
+    url: String,
+}
+
+impl Default for MetadataServer {
+    fn default() -> Self {
+        Self {
+            url: METADATA_TOKEN_URL.to_string(),
+        }
+    }
+}
+
+impl MetadataServer {
+    /// Fetches tokens from `url` instead of the metadata server, e.g. a local stub.
+    pub fn with_url(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+        }
+    }
+}
+
+impl TokenProvider for MetadataServer {
+    fn fetch<'a>(&'a self, http: &'a HttpClient) -> BoxFuture<'a, Result<AccessToken>> {
+        Box::pin(async move {
+            let res = http
+                .get(&self.url)
+                .header("Metadata-Flavor", "Google")
+                .send()
+                .await?;
+            token_response(res).await
+        })
+    }
+}
+
+/// The fields of a service-account key file used to sign tokens.
+#[derive(Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    #[serde(default)]
+    private_key_id: Option<String>,
+    token_uri: String,
+}
+
+/// A service account exchanging self-signed JWTs for access tokens.
+#[derive(Clone)]
+pub struct ServiceAccount {
+    client_email: String,
+    private_key_id: Option<String>,
+    token_uri: String,
+    scopes: Vec<String>,
+    signing_key: SigningKey<Sha256>,
+}
+
+impl Debug for ServiceAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceAccount")
+            .field("client_email", &self.client_email)
+            .field("token_uri", &self.token_uri)
+            .field("scopes", &self.scopes)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ServiceAccount {
+    /// Parses a JSON key file as downloaded from the Google Cloud console.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let key: ServiceAccountKey = serde_json::from_str(json)?;
+        let private_key = RsaPrivateKey::from_pkcs8_pem(&key.private_key)
+            .map_err(|e| anyhow!("Invalid service account private key: {}", e))?;
+
+        Ok(Self {
+            client_email: key.client_email,
+            private_key_id: key.private_key_id,
+            token_uri: key.token_uri,
+            scopes: vec![CLOUD_PLATFORM_SCOPE.to_string()],
+            signing_key: SigningKey::new(private_key),
+        })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Requests `scopes` instead of `CLOUD_PLATFORM_SCOPE`.
+    pub fn with_scopes(mut self, scopes: &[&str]) -> Self {
+        self.scopes = scopes.iter().map(|scope| scope.to_string()).collect();
+        self
+    }
+
+    pub fn client_email(&self) -> &str {
+        &self.client_email
+    }
+
+    /// Signs an RS256 JWT asserting the service account identity for an hour.
+    fn assertion(&self) -> Result<String> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let header = match &self.private_key_id {
+            Some(kid) => json!({ "alg": "RS256", "typ": "JWT", "kid": kid }),
+            None => json!({ "alg": "RS256", "typ": "JWT" }),
+        };
+        let claims = json!({
+            "iss": self.client_email,
+            "scope": self.scopes.join(" "),
+            "aud": self.token_uri,
+            "iat": now,
+            "exp": now + 3600,
+        });
+
+        let message = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let signature = self.signing_key.sign(message.as_bytes()).to_bytes();
+        Ok(format!("{}.{}", message, URL_SAFE_NO_PAD.encode(signature)))
+    }
+}
+
+impl TokenProvider for ServiceAccount {
+    fn fetch<'a>(&'a self, http: &'a HttpClient) -> BoxFuture<'a, Result<AccessToken>> {
+        Box::pin(async move {
+            let assertion = self.assertion()?;
+            let res = http
+                .post(&self.token_uri)
+                .form(&[
+                    ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                    ("assertion", assertion.as_str()),
+                ])
+                .send()
+                .await?;
+            token_response(res).await
+        })
+    }
+}
+
+/// A token provider with the last token it returned.
+#[derive(Debug)]
+pub(crate) struct Credentials {
+    provider: Box<dyn TokenProvider>,
+    token: Mutex<Option<AccessToken>>,
+}
+
+impl Credentials {
+    pub(crate) fn new(provider: Box<dyn TokenProvider>) -> Self {
+        Self {
+            provider,
+            token: Mutex::new(None),
+        }
+    }
+
+    /// Returns the cached token, fetching a new one once it is about to expire.
+    pub(crate) async fn token(&self, http: &HttpClient) -> Result<String> {
+        let mut cached = self.token.lock().await;
+        if let Some(token) = cached.as_ref().filter(|token| token.is_fresh()) {
+            return Ok(token.token.clone());
+        }
+
+        let token = self.provider.fetch(http).await?;
+        let value = token.token.clone();
+        *cached = Some(token);
+        Ok(value)
+    }
+}
@@ -1,23 +1,73 @@
-use crate::client::Client;
+use crate::client::{Client, Transport};
 use crate::messages::Message;
 use crate::models::Model;
+use crate::openai;
 use crate::requests::Content;
 use crate::requests::GeminiRequest;
-use crate::requests::Part;
+use crate::requests::ThinkingConfig;
+use crate::requests::Tool;
 use crate::responses::GeminiResponse;
 use crate::traits::CTrait;
+use crate::utils::{error_for_status, sse_events};
 use anyhow::anyhow;
 use anyhow::Result;
 use derive_builder::Builder;
+use futures_util::future::Either;
+use futures_util::{Stream as FuturesStream, TryStreamExt};
 use reqwest::Method;
 
 #[derive(Builder, Default, Clone)]
 #[builder(setter(into), default)]
 pub struct Chat {
-    pub model: Model,
+    #[builder(setter(into, strip_option), default)]
+    pub model: Option<Model>,
     pub messages: Vec<Message>,
     #[builder(setter(into, strip_option), default)]
     pub system: Option<Vec<Message>>,
+    #[builder(setter(into, strip_option), default)]
+    pub tools: Option<Vec<Tool>>,
+    /// Name of cached content used as context, e.g. `cachedContents/abc`.
+    #[builder(setter(into, strip_option), default)]
+    pub cached_content: Option<String>,
+    /// Maximum number of thinking tokens of thinking models.
+    #[builder(setter(into, strip_option), default)]
+    pub thinking_budget: Option<i32>,
+    /// Returns thought summaries, see `GeminiResponse::thoughts`.
+    #[builder(setter(into, strip_option), default)]
+    pub include_thoughts: Option<bool>,
+}
+
+impl Chat {
+    /// Builds the `generateContent` request body, grouping consecutive messages of the
+    /// same role into conversation turns.
+    pub fn to_request(&self, model: &Model) -> GeminiRequest {
+        let mut contents: Vec<Content> = Vec::new();
+        for message in &self.messages {
+            match contents.last_mut() {
+                Some(turn) if turn.role.as_deref() == Some(message.role()) => {
+                    turn.parts.push(message.to_part())
+                }
+                _ => contents.push(Content {
+                    parts: vec![message.to_part()],
+                    role: Some(message.role().to_string()),
+                }),
+            }
+        }
+
+        let system_instruction = self.system.as_ref().map(|messages| Content {
+            parts: messages.iter().map(|msg| msg.to_part()).collect(),
+            role: None,
+        });
+
+        GeminiRequest {
+            model: model.to_string(),
+            contents,
+            system_instruction,
+            config: ThinkingConfig::config(self.thinking_budget, self.include_thoughts),
+            tools: self.tools.clone(),
+            cached_content: self.cached_content.clone(),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -26,37 +76,87 @@ pub struct Chats {
 }
 
 impl Chats {
+    /// Returns the answer of the model, without thoughts.
     pub async fn generate(&self, params: Chat) -> Result<String> {
-        let content = Content {
-            parts: params.messages.iter().map(|msg| msg.to_part()).collect(),
+        let response = self.generate_response(params).await?;
+        if response.candidates.is_none() {
+            return Err(anyhow!("Missing candidates"));
+        }
+        response
+            .text()
+            .ok_or_else(|| anyhow!("Expected text response"))
+    }
+
+    /// Returns the whole response, including thoughts and usage metadata.
+    pub async fn generate_response(&self, params: Chat) -> Result<GeminiResponse> {
+        let (model, request_body) = self.prepare(params).await?;
+
+        let json = match self.client.transport() {
+            Transport::Gemini => {
+                let req = self
+                    .client
+                    .request_with_model(Method::POST, &model, "generateContent")?
+                    .json(&request_body);
+
+                let res = error_for_status(self.client.send(req).await?).await?;
+                res.json::<GeminiResponse>().await?
+            }
+            Transport::OpenAi => openai::generate(&self.client, &model, &request_body).await?,
         };
+        self.client
+            .record_usage(&model, json.usage_metadata.as_ref())?;
 
-        let system_instruction = params.system.as_ref().map(|messages| Content {
-            parts: messages.iter().map(|msg| msg.to_part()).collect(),
-        });
+        Ok(json)
+    }
 
-        let request_body = GeminiRequest {
-            model: params.model.to_string(),
-            contents: vec![content],
-            system_instruction,
-            config: None,
+    /// Streams the answer to the conversation as parsed chunks over server-sent events.
+    pub async fn stream(
+        &self,
+        params: Chat,
+    ) -> Result<impl FuturesStream<Item = Result<GeminiResponse>>> {
+        let (model, request_body) = self.prepare(params).await?;
+
+        let chunks = match self.client.transport() {
+            Transport::Gemini => {
+                let req = self
+                    .client
+                    .request_with_model(Method::POST, &model, "streamGenerateContent")?
+                    .query(&[("alt", "sse")])
+                    .json(&request_body);
+
+                let res = error_for_status(self.client.send(req).await?).await?;
+                Either::Left(
+                    sse_events(res)
+                        .and_then(|data| async move { Ok(serde_json::from_str(&data)?) }),
+                )
+            }
+            Transport::OpenAi => {
+                Either::Right(openai::stream(&self.client, &model, &request_body).await?)
+            }
         };
 
-        let req = self
-            .client
-            .request(Method::POST, "generateContent")?
-            .json(&request_body);
+        let client = self.client.clone();
+        Ok(chunks.and_then(move |json: GeminiResponse| {
+            let recorded = client.record_chunk_usage(&model, &json);
+            async move { recorded.map(|_| json) }
+        }))
+    }
 
-        let res = req.send().await?;
-        let json: GeminiResponse = res.json().await?;
+    async fn prepare(&self, params: Chat) -> Result<(Model, GeminiRequest)> {
+        let model = params
+            .model
+            .clone()
+            .unwrap_or_else(|| self.client.get_model());
+        let request_body = params.to_request(&model);
 
-        let candidates = json
-            .candidates
-            .ok_or_else(|| anyhow!("Missing candidates"))?;
+        self.client
+            .ensure_supports(&model, "generateContent")
+            .await?;
+        self.client
+            .ensure_within_limit(&model, &request_body)
+            .await?;
+        self.client.throttle(&model, &request_body).await?;
 
-        match &candidates[0].content.parts[0] {
-            Part::Text { text } => Ok(text.clone()),
-            _ => Err(anyhow!("Expected text response")),
-        }
+        Ok((model, request_body))
     }
 }
@@ -46,6 +46,9 @@ FUNCTIONALITIES:
   - Batch Embed Contents: Batch embed contents using the Gemini API.
   - Get Model Info: Get information about the model from the Gemini API.
   - List Models: List available models from the Gemini API.
+  - Ask: Answer questions from a directory of text documents.
+  - Usage: Summarize the token usage and estimated cost of logged calls.
+  - Serve: Serve an OpenAI-compatible API backed by Gemini.
 
 USAGE:
   gems [OPTIONS] <COMMAND>
@@ -60,6 +63,12 @@ EXAMPLES:
   Stream generate content:
     gems stream -t "Generate a short fictional story"
 
+  Show the thoughts of a thinking model:
+    gems -m gemini-2.5-pro-preview-03-25 generate --thoughts --thinking-budget 1024 -t "Is 97 prime?"
+
+  Ground an answer on Google Search:
+    gems generate --search -t "Who won the latest Champions League final?"
+
   Vision:
     gems vision -i /path/to/your/image.jpg
 
@@ -69,9 +78,15 @@ EXAMPLES:
   Embed content:
     gems -m 'embedding-001' embed -t "Write a story about a magic backpack."
 
+  Validate the model before calling it:
+    gems --preflight -m 'embedding-001' generate -t "Hello"
+
   Batch embed contents:
     gems -m 'embedding-001' batch -t "Write a story about a magic backpack.","Generate a poem about nature."
 
+  Embed a document for retrieval:
+    gems embed --task-type RETRIEVAL_DOCUMENT --title "Backpacks" --dimensions 256 -t "A magic backpack."
+
   Generate an Image:
     gems imagen -t "Yo, create a 3d rendered image of a cat with wings."
 
@@ -81,12 +96,28 @@ EXAMPLES:
   Text to speech:
     gems tts -t "Yo, Say 'Hello' with a humble bumble voice!."
 
+  Stream text to speech:
+    gems tts --stream -t "Yo, Say 'Hello' with a humble bumble voice!."
+
   Get model info:
     gems info
 
   List models:
     gems list
 
+  List embedding models:
+    gems list --method embedContent
+
+  Log token usage and summarize the estimated cost:
+    gems --usage-log usage.jsonl generate -t "Hello"
+    gems usage --log usage.jsonl
+
+  Serve an OpenAI-compatible API (requires the `server` feature):
+    gems serve --port 8080
+
+  Answer a question from local documents:
+    gems ask --corpus ./docs "How do I configure the proxy?"
+
   TUI mode:
     gems
 
@@ -100,6 +131,12 @@ pub struct Cli {
     /// Model to be used.
     #[arg(short, long)]
     pub model: Option<String>,
+    /// Check model capabilities and token limits before sending requests.
+    #[arg(long)]
+    pub preflight: bool,
+    /// Append the token usage of every call to this file.
+    #[arg(long)]
+    pub usage_log: Option<String>,
     #[command(subcommand)]
     pub cmd: Option<Command>,
 }
@@ -118,6 +155,10 @@ pub enum Command {
     Imagen(Imagen),
     Vidgen(Vidgen),
     Tts(Tts),
+    Ask(Ask),
+    Usage(Usage),
+    #[cfg(feature = "server")]
+    Serve(Serve),
 }
 
 #[cfg(feature = "cli")]
@@ -126,6 +167,10 @@ pub struct Generate {
     /// The text to generate content from.
     #[arg(short, long)]
     pub text: String,
+    #[command(flatten)]
+    pub thinking: ThinkingOptions,
+    #[command(flatten)]
+    pub tools: ToolOptions,
 }
 
 #[cfg(feature = "cli")]
@@ -134,6 +179,35 @@ pub struct Stream {
     /// The text to generate content from.
     #[arg(short, long)]
     pub text: String,
+    #[command(flatten)]
+    pub thinking: ThinkingOptions,
+    #[command(flatten)]
+    pub tools: ToolOptions,
+}
+
+#[cfg(feature = "cli")]
+#[derive(Args, Debug, Clone)]
+pub struct ThinkingOptions {
+    /// Maximum number of thinking tokens, 0 disables thinking where supported.
+    #[arg(long)]
+    pub thinking_budget: Option<i32>,
+    /// Print the model's thought summaries in a dimmed style.
+    #[arg(long)]
+    pub thoughts: bool,
+}
+
+#[cfg(feature = "cli")]
+#[derive(Args, Debug, Clone)]
+pub struct ToolOptions {
+    /// Ground the answer on Google Search results and cite the sources.
+    #[arg(long)]
+    pub search: bool,
+    /// Let the model read the URLs given in the prompt.
+    #[arg(long)]
+    pub url_context: bool,
+    /// Let the model write and run Python code.
+    #[arg(long)]
+    pub code_execution: bool,
 }
 
 #[cfg(feature = "cli")]
@@ -150,6 +224,8 @@ pub struct Embed {
     /// The text to embed content from.
     #[arg(short, long)]
     pub text: String,
+    #[command(flatten)]
+    pub options: EmbedOptions,
 }
 
 #[cfg(feature = "cli")]
@@ -158,6 +234,22 @@ pub struct Batch {
     /// List of texts to embed contents from.
     #[arg(short, long)]
     pub texts: Vec<String>,
+    #[command(flatten)]
+    pub options: EmbedOptions,
+}
+
+#[cfg(feature = "cli")]
+#[derive(Args, Debug, Clone)]
+pub struct EmbedOptions {
+    /// The task the embedding is used for, e.g. RETRIEVAL_QUERY or RETRIEVAL_DOCUMENT.
+    #[arg(long)]
+    pub task_type: Option<crate::requests::TaskType>,
+    /// The document title, used with RETRIEVAL_DOCUMENT.
+    #[arg(long)]
+    pub title: Option<String>,
+    /// Truncate the embedding to this many dimensions.
+    #[arg(long)]
+    pub dimensions: Option<u32>,
 }
 
 #[cfg(feature = "cli")]
@@ -166,7 +258,11 @@ pub struct Info {}
 
 #[cfg(feature = "cli")]
 #[derive(Args, Debug, Clone)]
-pub struct List {}
+pub struct List {
+    /// Only list models supporting this generation method, e.g. `embedContent`.
+    #[arg(long)]
+    pub method: Option<String>,
+}
 
 #[cfg(feature = "cli")]
 #[derive(Args, Debug, Clone)]
@@ -204,4 +300,48 @@ pub struct Tts {
     /// The voice.
     #[arg(short, long, default_value_t = String::from("Kore"))]
     pub voice: String,
+    /// Write audio chunks to the output file as they arrive.
+    #[arg(short, long)]
+    pub stream: bool,
+}
+
+#[cfg(feature = "cli")]
+#[derive(Args, Debug, Clone)]
+pub struct Usage {
+    /// The usage log written with `--usage-log`, that same file by default.
+    #[arg(short, long)]
+    pub log: Option<String>,
+}
+
+#[cfg(all(feature = "cli", feature = "server"))]
+#[derive(Args, Debug, Clone)]
+pub struct Serve {
+    /// The address to listen on.
+    #[arg(long, default_value_t = String::from("127.0.0.1"))]
+    pub host: String,
+    /// The port to listen on.
+    #[arg(short, long, default_value_t = 8080)]
+    pub port: u16,
+}
+
+#[cfg(feature = "cli")]
+#[derive(Args, Debug, Clone)]
+pub struct Ask {
+    /// The question to answer.
+    pub question: String,
+    /// Directory of `.txt` and `.md` documents to answer from.
+    #[arg(short, long)]
+    pub corpus: String,
+    /// Index file reused across runs, created from the corpus when missing.
+    #[arg(short, long)]
+    pub index: Option<String>,
+    /// Number of chunks given to the model as context.
+    #[arg(short = 'k', long, default_value_t = 4)]
+    pub top_k: usize,
+    /// Maximum number of characters per chunk.
+    #[arg(long, default_value_t = 1000)]
+    pub chunk_size: usize,
+    /// Number of characters shared by consecutive chunks.
+    #[arg(long, default_value_t = 200)]
+    pub overlap: usize,
 }
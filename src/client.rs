@@ -1,12 +1,22 @@
+use crate::auth::{Credentials, MetadataServer, TokenProvider};
 use crate::chat::Chats;
+use crate::cost::UsageTracker;
+use crate::embed::cache::EmbeddingCache;
 use crate::embed::Embeddings;
 use crate::imagen::Images;
+use crate::live::Lives;
 use crate::models::Model;
 use crate::models::Models;
+use crate::openai::GEMINI_OPENAI_URL;
+use crate::rag::Rags;
+use crate::ratelimit::{estimate_tokens, RateLimiter};
+use crate::requests::GeminiRequest;
+use crate::responses::{GeminiResponse, ModelInfo, UsageMetadata};
 use crate::stream::Streaming;
 use crate::tokens::Tokens;
 use crate::traits::CTrait;
 use crate::tts::Tts;
+use crate::utils::redact_url;
 use crate::vidgen::Videos;
 use crate::vision::Visions;
 use anyhow::anyhow;
@@ -14,25 +24,353 @@ use anyhow::Result;
 use reqwest::Client as HttpClient;
 use reqwest::Method;
 use reqwest::RequestBuilder;
+use reqwest::Response;
 use reqwest::Url;
+use std::collections::HashMap;
+use std::fmt;
 use std::str::FromStr;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
-const GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+pub use reqwest::{Certificate, Identity, Proxy};
 
-#[derive(Debug, Clone, Default)]
+const GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+/// The header carrying the API key of the Gemini API.
+pub const API_KEY_HEADER: &str = "x-goog-api-key";
+
+const GEMINI_LIVE_URL: &str = "wss://generativelanguage.googleapis.com/ws/google.ai.generativelanguage.v1beta.GenerativeService.BidiGenerateContent";
+
+/// The service behind the native API.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// The Gemini Developer API, authenticated with an API key.
+    #[default]
+    GeminiApi,
+    /// Vertex AI, authenticated with OAuth access tokens from `CBuilder::credentials`.
+    ///
+    /// Models are addressed as `projects/{project}/locations/{location}/publishers/google/models/{model}`
+    /// and `tunedModels/{id}` as the `projects/{project}/locations/{location}/endpoints/{id}`
+    /// endpoint it is deployed to.
+    /// A `base_url` replaces `https://{location}-aiplatform.googleapis.com/v1`.
+    VertexAi { project: String, location: String },
+}
+
+/// The wire format a `Client` speaks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Transport {
+    /// The native Gemini API.
+    #[default]
+    Gemini,
+    /// The OpenAI Chat Completions and Embeddings API, see `openai`.
+    ///
+    /// Only `chat` and `embeddings` are available; other sub-clients fail. The base URL
+    /// defaults to Gemini's compatibility layer and the API key is sent as a bearer token.
+    OpenAi,
+}
+
+#[derive(Clone, Default)]
 #[allow(dead_code)]
 pub struct Client {
     http_client: Arc<HttpClient>,
     api_key: Arc<RwLock<Option<String>>>,
     model: Arc<RwLock<Model>>,
-    base_url: String,
+    base_url: Option<String>,
+    live_url: Option<String>,
+    transport: Transport,
+    backend: Backend,
+    credentials: Option<Arc<Credentials>>,
+    api_key_in_query: bool,
+    model_cache: Arc<RwLock<HashMap<Model, ModelInfo>>>,
+    preflight: bool,
+    embedding_cache: Option<Arc<dyn EmbeddingCache>>,
+    usage_tracker: Option<UsageTracker>,
+    rate_limiter: Option<RateLimiter>,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let api_key = self.get_api_key().map(|_| "<redacted>");
+        f.debug_struct("Client")
+            .field("api_key", &api_key)
+            .field("model", &self.get_model())
+            .field("base_url", &self.base_url)
+            .field("live_url", &self.live_url)
+            .field("transport", &self.transport)
+            .field("backend", &self.backend)
+            .field("credentials", &self.credentials)
+            .field("preflight", &self.preflight)
+            .field("embedding_cache", &self.embedding_cache)
+            .field("usage_tracker", &self.usage_tracker)
+            .field("rate_limiter", &self.rate_limiter)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Client {
     pub fn builder() -> CBuilder {
         CBuilder::default()
     }
+
+    /// Returns a client sharing this one's state whose calls are recorded by `tracker`.
+    pub fn with_usage_tracker(&self, tracker: UsageTracker) -> Client {
+        Client {
+            usage_tracker: Some(tracker),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client sharing this one's state but calling the API with `api_key`.
+    ///
+    /// Unlike `set_api_key`, the key of this client and its other clones is left untouched.
+    pub fn with_api_key(&self, api_key: &str) -> Client {
+        Client {
+            api_key: Arc::new(RwLock::new(Some(api_key.to_string()))),
+            ..self.clone()
+        }
+    }
+
+    pub fn usage_tracker(&self) -> Option<&UsageTracker> {
+        self.usage_tracker.as_ref()
+    }
+
+    pub fn transport(&self) -> Transport {
+        self.transport
+    }
+
+    pub fn backend(&self) -> &Backend {
+        &self.backend
+    }
+
+    /// Whether requests authenticate with bearer tokens from `CBuilder::credentials`
+    /// instead of an API key.
+    pub fn has_credentials(&self) -> bool {
+        self.credentials.is_some()
+    }
+
+    /// Waits for the rate limiter, if any, to let a generation request through.
+    pub(crate) async fn throttle(&self, model: &Model, request: &GeminiRequest) -> Result<()> {
+        let Some(limiter) = &self.rate_limiter else {
+            return Ok(());
+        };
+
+        let tokens = if limiter.counts_tokens() && self.transport == Transport::Gemini {
+            let count = self.tokens().count_request(model, request).await?;
+            count.total_tokens.max(0) as u32
+        } else {
+            estimate_tokens(request.contents.iter().chain(&request.system_instruction))
+        };
+        limiter.acquire(model, tokens).await
+    }
+
+    /// Waits for the rate limiter, if any, to let a request of `tokens` through.
+    pub(crate) async fn throttle_tokens(&self, model: &Model, tokens: u32) -> Result<()> {
+        match &self.rate_limiter {
+            Some(limiter) => limiter.acquire(model, tokens).await,
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn record_usage(&self, model: &Model, usage: Option<&UsageMetadata>) -> Result<()> {
+        if let (Some(tracker), Some(usage)) = (&self.usage_tracker, usage) {
+            tracker.record(model, usage)?;
+        }
+        Ok(())
+    }
+
+    /// Records the usage of a streamed chunk if it is the final one. Chunks carry the
+    /// cumulative usage, so only the one with a finish reason counts, or the trailing
+    /// usage-only chunk of OpenAI.
+    pub(crate) fn record_chunk_usage(&self, model: &Model, chunk: &GeminiResponse) -> Result<()> {
+        let last = match &chunk.candidates {
+            Some(candidates) => candidates.iter().any(|c| c.finish_reason.is_some()),
+            None => true,
+        };
+        if last {
+            self.record_usage(model, chunk.usage_metadata.as_ref())?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn base_url(&self) -> String {
+        let url = match (&self.base_url, self.transport, &self.backend) {
+            (Some(url), _, _) => url.clone(),
+            (None, Transport::OpenAi, _) => GEMINI_OPENAI_URL.to_string(),
+            (None, Transport::Gemini, Backend::GeminiApi) => GEMINI_BASE_URL.to_string(),
+            (None, Transport::Gemini, Backend::VertexAi { location, .. }) => {
+                vertex_base_url(location)
+            }
+        };
+        url.trim_end_matches('/').to_string()
+    }
+
+    /// Builds a request for an API resource path relative to the base URL,
+    /// e.g. `models/gemini-2.0-flash` or the name of a long-running operation.
+    pub(crate) fn resource_request(&self, method: Method, path: &str) -> Result<RequestBuilder> {
+        if self.transport != Transport::Gemini {
+            return Err(anyhow!(
+                "{} is not available with the {:?} transport",
+                path,
+                self.transport
+            ));
+        }
+        let mut api_key = None;
+        let parsed_url = match &self.backend {
+            Backend::GeminiApi => {
+                let full_url = format!("{}/{}", self.base_url(), path);
+                if self.credentials.is_none() {
+                    api_key = Some(self.get_api_key().ok_or(anyhow!("API key not set"))?);
+                }
+                match &api_key {
+                    Some(key) if self.api_key_in_query => {
+                        Url::parse_with_params(&full_url, &[("key", key)])?
+                    }
+                    _ => Url::parse(&full_url)?,
+                }
+            }
+            Backend::VertexAi { project, location } => {
+                if let Some(feature) = vertex_unsupported(path) {
+                    return Err(anyhow!("{} is not supported on Vertex AI", feature));
+                }
+                // Operation names returned by the API are already fully qualified, and
+                // tuned models are served from the endpoint they were deployed to.
+                let path = if path.starts_with("projects/") {
+                    path.to_string()
+                } else if let Some(endpoint) = path.strip_prefix("tunedModels/") {
+                    format!(
+                        "projects/{}/locations/{}/endpoints/{}",
+                        project, location, endpoint
+                    )
+                } else {
+                    format!(
+                        "projects/{}/locations/{}/publishers/google/{}",
+                        project, location, path
+                    )
+                };
+                Url::parse(&format!("{}/{}", self.base_url(), path))?
+            }
+        };
+
+        let req = self
+            .http_client
+            .request(method, parsed_url)
+            .header("Content-Type", "application/json");
+        Ok(match api_key {
+            Some(key) if !self.api_key_in_query => req.header(API_KEY_HEADER, key),
+            _ => req,
+        })
+    }
+
+    /// The API key to send in the `x-goog-api-key` header of the Live API handshake, or
+    /// in its URL with `api_key_in_query`.
+    pub(crate) fn live_api_key(&self) -> Result<(String, bool)> {
+        let api_key = self.get_api_key().ok_or(anyhow!("API key not set"))?;
+        Ok((api_key, self.api_key_in_query))
+    }
+
+    /// Sends a request built by `request_with_model`, `resource_request` or
+    /// `openai_request`, with a bearer token when the client has credentials.
+    pub(crate) async fn send(&self, req: RequestBuilder) -> Result<Response> {
+        let req = match &self.credentials {
+            Some(credentials) => req.bearer_auth(credentials.token(&self.http_client).await?),
+            None => req,
+        };
+        req.send().await.map_err(|mut error| {
+            if let Some(url) = error.url_mut() {
+                redact_url(url);
+            }
+            error.into()
+        })
+    }
+
+    /// Builds a POST request for an OpenAI-compatible endpoint, e.g. `chat/completions`.
+    ///
+    /// The API key is optional since local servers often do not check it, and left out
+    /// when `send` authenticates with credentials instead.
+    pub(crate) fn openai_request(&self, path: &str) -> RequestBuilder {
+        let req = self
+            .http_client
+            .post(format!("{}/{}", self.base_url(), path));
+        match self.get_api_key().filter(|_| self.credentials.is_none()) {
+            Some(api_key) => req.bearer_auth(api_key),
+            None => req,
+        }
+    }
+
+    pub(crate) fn live_url(&self) -> &str {
+        self.live_url.as_deref().unwrap_or(GEMINI_LIVE_URL)
+    }
+
+    pub(crate) fn embedding_cache(&self) -> Option<&dyn EmbeddingCache> {
+        self.embedding_cache.as_deref()
+    }
+
+    pub(crate) fn cached_model_info(&self, model: &Model) -> Option<ModelInfo> {
+        self.model_cache.read().unwrap().get(model).cloned()
+    }
+
+    pub(crate) fn cache_model_info(&self, model: &Model, info: ModelInfo) {
+        self.model_cache
+            .write()
+            .unwrap()
+            .insert(model.clone(), info);
+    }
+
+    /// Vertex AI has no model metadata endpoint here, so preflight is skipped there.
+    fn preflight_enabled(&self) -> bool {
+        self.preflight && self.transport == Transport::Gemini && self.backend == Backend::GeminiApi
+    }
+
+    /// Fails early if preflight validation is enabled and `model` does not support `method`.
+    pub(crate) async fn ensure_supports(&self, model: &Model, method: &str) -> Result<()> {
+        if !self.preflight_enabled() {
+            return Ok(());
+        }
+
+        let info = self.models().info(model).await?;
+        if info.supports(method) {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Model {} does not support {} (supported: {})",
+                model.to_string(),
+                method,
+                info.supported_generation_methods.join(", ")
+            ))
+        }
+    }
+
+    /// Fails early if preflight validation is enabled and the prompt of `request` exceeds the
+    /// input token limit of `model`.
+    pub(crate) async fn ensure_within_limit(
+        &self,
+        model: &Model,
+        request: &GeminiRequest,
+    ) -> Result<()> {
+        if !self.preflight_enabled() {
+            return Ok(());
+        }
+
+        let info = self.models().info(model).await?;
+        if info.input_token_limit <= 0 || !info.supports("countTokens") {
+            return Ok(());
+        }
+
+        let count = self
+            .tokens()
+            .count_request(model, request)
+            .await?
+            .total_tokens;
+        if count > info.input_token_limit {
+            Err(anyhow!(
+                "Prompt has {} tokens, exceeding the input token limit of {} for {}",
+                count,
+                info.input_token_limit,
+                model.to_string()
+            ))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 impl CTrait for Client {
@@ -54,26 +392,22 @@ impl CTrait for Client {
     }
 
     fn request(&self, method: Method, endpoint: &str) -> Result<RequestBuilder> {
-        let api_key = self.get_api_key().ok_or(anyhow!("API key not set"))?;
+        self.request_with_model(method, &self.get_model(), endpoint)
+    }
 
-        let full_url = if endpoint == "models" {
-            GEMINI_BASE_URL.to_string()
+    fn request_with_model(
+        &self,
+        method: Method,
+        model: &Model,
+        endpoint: &str,
+    ) -> Result<RequestBuilder> {
+        if endpoint == "models" {
+            self.resource_request(method, "models")
         } else if endpoint.is_empty() {
-            format!("{}/{}", GEMINI_BASE_URL, self.get_model().to_string())
+            self.resource_request(method, &model.resource_name())
         } else {
-            format!(
-                "{}/{}:{}",
-                GEMINI_BASE_URL,
-                self.get_model().to_string(),
-                endpoint
-            )
-        };
-        let parsed_url = Url::parse_with_params(&full_url, &[("key", api_key)]).unwrap();
-
-        Ok(self
-            .http_client
-            .request(method, parsed_url)
-            .header("Content-Type", "application/json"))
+            self.resource_request(method, &format!("{}:{}", model.resource_name(), endpoint))
+        }
     }
 
     fn chat(&self) -> Chats {
@@ -128,17 +462,103 @@ impl CTrait for Client {
             client: self.clone(),
         }
     }
+
+    fn live(&self) -> Lives {
+        Lives {
+            client: self.clone(),
+        }
+    }
+
+    fn rag(&self) -> Rags {
+        Rags {
+            client: self.clone(),
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct CBuilder {
-    model: Option<Model>,
+    model: Option<String>,
     base_url: Option<String>,
+    live_url: Option<String>,
+    transport: Transport,
+    backend: Backend,
+    credentials: Option<Box<dyn TokenProvider>>,
+    api_key_in_query: bool,
+    preflight: bool,
+    embedding_cache: Option<Arc<dyn EmbeddingCache>>,
+    usage_tracker: Option<UsageTracker>,
+    rate_limiter: Option<RateLimiter>,
+    http: HttpOptions,
+    http_client: Option<HttpClient>,
+}
+
+/// Settings of the HTTP client built by `CBuilder::build`.
+#[derive(Default)]
+struct HttpOptions {
+    root_certificates: Vec<Certificate>,
+    identity: Option<Identity>,
+    proxies: Vec<Proxy>,
+    no_proxy: bool,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    user_agent: Option<String>,
+    accept_invalid_certs: bool,
+}
+
+impl HttpOptions {
+    fn is_default(&self) -> bool {
+        self.root_certificates.is_empty()
+            && self.identity.is_none()
+            && self.proxies.is_empty()
+            && !self.no_proxy
+            && self.connect_timeout.is_none()
+            && self.read_timeout.is_none()
+            && self.timeout.is_none()
+            && self.user_agent.is_none()
+            && !self.accept_invalid_certs
+    }
+
+    fn build(self) -> Result<HttpClient> {
+        let mut builder = HttpClient::builder();
+        for certificate in self.root_certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some(identity) = self.identity {
+            builder = builder.identity(identity);
+        }
+        for proxy in self.proxies {
+            builder = builder.proxy(proxy);
+        }
+        if self.no_proxy {
+            builder = builder.no_proxy();
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(user_agent) = self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if self.accept_invalid_certs {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        Ok(builder.build()?)
+    }
 }
 
 impl CBuilder {
+    /// Sets the default model by name, e.g. `gemini-2.0-flash` or `tunedModels/my-model`.
+    ///
+    /// Malformed names are reported by `build`.
     pub fn model(mut self, model: &str) -> Self {
-        self.model = Some(Model::from_str(model).unwrap_or_default());
+        self.model = Some(model.to_string());
         self
     }
 
@@ -147,18 +567,188 @@ impl CBuilder {
         self
     }
 
+    pub fn live_url(mut self, live_url: &str) -> Self {
+        self.live_url = Some(live_url.to_string());
+        self
+    }
+
+    /// Selects the wire format, `Transport::Gemini` by default.
+    pub fn transport(mut self, transport: Transport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Selects the service behind the native API, `Backend::GeminiApi` by default.
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Authenticates with access tokens from `provider` instead of an API key.
+    ///
+    /// Defaults to `auth::MetadataServer` on Vertex AI.
+    pub fn credentials(mut self, provider: impl TokenProvider + 'static) -> Self {
+        self.credentials = Some(Box::new(provider));
+        self
+    }
+
+    /// Sends the API key as the `key` query parameter instead of the `x-goog-api-key`
+    /// header, for proxies that drop custom headers. URLs then contain the key.
+    pub fn api_key_in_query(mut self, api_key_in_query: bool) -> Self {
+        self.api_key_in_query = api_key_in_query;
+        self
+    }
+
+    /// Validates requests against the cached model metadata before sending them, rejecting
+    /// unsupported generation methods and prompts over the input token limit. Ignored on
+    /// Vertex AI and with the OpenAI transport.
+    pub fn preflight(mut self, preflight: bool) -> Self {
+        self.preflight = preflight;
+        self
+    }
+
+    /// Serves embeddings of previously seen inputs from `cache` instead of the API.
+    pub fn embedding_cache(mut self, cache: impl EmbeddingCache + 'static) -> Self {
+        self.embedding_cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Records the usage metadata of generation calls, see `cost::UsageTracker`.
+    pub fn usage_tracker(mut self, tracker: UsageTracker) -> Self {
+        self.usage_tracker = Some(tracker);
+        self
+    }
+
+    /// Delays requests to stay within per-model quotas, see `ratelimit::RateLimiter`.
+    pub fn rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Trusts `certificate` in addition to the system roots, e.g. a corporate CA.
+    pub fn root_certificate(mut self, certificate: Certificate) -> Self {
+        self.http.root_certificates.push(certificate);
+        self
+    }
+
+    /// Presents `identity` as the client certificate of mutual TLS.
+    pub fn identity(mut self, identity: Identity) -> Self {
+        self.http.identity = Some(identity);
+        self
+    }
+
+    /// Routes requests through `proxy`. Without one, the `HTTP_PROXY`, `HTTPS_PROXY` and
+    /// `NO_PROXY` environment variables apply.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.http.proxies.push(proxy);
+        self
+    }
+
+    /// Ignores the proxy environment variables.
+    pub fn no_proxy(mut self) -> Self {
+        self.http.no_proxy = true;
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Fails a request when no data is received for `timeout`, streams included.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.http.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Fails a request that has not completed within `timeout`, body included.
+    ///
+    /// Long generations and streams may need more time than usual HTTP calls.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http.timeout = Some(timeout);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.http.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Accepts any TLS certificate, including expired or self-signed ones.
+    ///
+    /// Only meant for local testing: anyone on the network path can then read the API key.
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.http.accept_invalid_certs = accept_invalid_certs;
+        self
+    }
+
+    /// Sends requests with `client` instead of building one.
+    ///
+    /// Cannot be combined with the other HTTP options, which belong on `client`.
+    pub fn http_client(mut self, client: HttpClient) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
     pub fn build(self) -> Result<Client> {
-        let model = self.model.unwrap_or_default();
+        let model = match self.model {
+            Some(name) => Model::from_str(&name)?,
+            None => Model::default(),
+        };
+
+        let credentials = match (self.credentials, &self.backend) {
+            (Some(provider), _) => Some(provider),
+            (None, Backend::VertexAi { .. }) => Some(Box::new(MetadataServer::default()) as _),
+            (None, Backend::GeminiApi) => None,
+        };
+
+        let http_client = match self.http_client {
+            Some(_) if !self.http.is_default() => {
+                return Err(anyhow!(
+                    "HTTP options cannot be combined with a custom http_client"
+                ))
+            }
+            Some(client) => client,
+            None => self.http.build()?,
+        };
 
         Ok(Client {
-            http_client: Arc::new(
-                HttpClient::builder()
-                    .danger_accept_invalid_certs(true)
-                    .build()?,
-            ),
+            http_client: Arc::new(http_client),
             api_key: Arc::new(RwLock::new(None)),
             model: Arc::new(RwLock::new(model)),
-            base_url: self.base_url.unwrap_or_else(|| GEMINI_BASE_URL.to_string()),
+            base_url: self.base_url,
+            live_url: self.live_url,
+            transport: self.transport,
+            backend: self.backend,
+            credentials: credentials.map(|provider| Arc::new(Credentials::new(provider))),
+            api_key_in_query: self.api_key_in_query,
+            model_cache: Arc::new(RwLock::new(HashMap::new())),
+            preflight: self.preflight,
+            embedding_cache: self.embedding_cache,
+            usage_tracker: self.usage_tracker,
+            rate_limiter: self.rate_limiter,
         })
     }
 }
+
+/// Names the feature behind `path` when its Vertex AI endpoint or body differs from the
+/// Gemini API one.
+fn vertex_unsupported(path: &str) -> Option<&'static str> {
+    match path.rsplit_once(':').map(|(_, method)| method) {
+        Some("embedContent" | "batchEmbedContents") => Some("Embedding"),
+        Some("predictLongRunning") => Some("Video generation"),
+        Some(_) => None,
+        None if path.starts_with("models") || path.starts_with("tunedModels") => {
+            Some("Model metadata")
+        }
+        None => None,
+    }
+}
+
+fn vertex_base_url(location: &str) -> String {
+    if location == "global" {
+        "https://aiplatform.googleapis.com/v1".to_string()
+    } else {
+        format!("https://{}-aiplatform.googleapis.com/v1", location)
+    }
+}
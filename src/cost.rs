@@ -0,0 +1,283 @@
+use crate::models::Model;
+use crate::responses::UsageMetadata;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Prices in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pricing {
+    pub input: f64,
+    pub cached_input: f64,
+    /// Applies to candidate and thinking tokens.
+    pub output: f64,
+}
+
+impl Pricing {
+    pub const fn new(input: f64, cached_input: f64, output: f64) -> Self {
+        Self {
+            input,
+            cached_input,
+            output,
+        }
+    }
+
+    /// Published paid-tier prices for prompts under the long context threshold.
+    ///
+    /// Returns `None` for custom models and for models billed per image or per second.
+    pub fn for_model(model: &Model) -> Option<Self> {
+        match model {
+            Model::Pro25Preview => Some(Self::new(1.25, 0.31, 10.0)),
+            Model::Flash20 => Some(Self::new(0.10, 0.025, 0.40)),
+            Model::Flash20Lite => Some(Self::new(0.075, 0.075, 0.30)),
+            Model::Flash15 => Some(Self::new(0.075, 0.01875, 0.30)),
+            Model::Flash15_8B => Some(Self::new(0.0375, 0.01, 0.15)),
+            Model::Pro15 => Some(Self::new(1.25, 0.3125, 5.0)),
+            Model::Flash20Live => Some(Self::new(0.35, 0.35, 1.50)),
+            Model::Tts => Some(Self::new(0.50, 0.50, 10.0)),
+            Model::Embedding | Model::FlashExpImage => Some(Self::new(0.0, 0.0, 0.0)),
+            Model::Imagen3 | Model::Veo2 | Model::Custom(_) => None,
+        }
+    }
+}
+
+/// The estimated cost of a call in USD.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cost {
+    pub input: f64,
+    pub cached_input: f64,
+    pub output: f64,
+}
+
+impl Cost {
+    pub fn total(&self) -> f64 {
+        self.input + self.cached_input + self.output
+    }
+}
+
+/// Estimates the cost of a call from its usage metadata.
+pub fn estimate(pricing: &Pricing, usage: &UsageMetadata) -> Cost {
+    let tokens = TokenUsage::from(usage);
+    let per_token = |price: f64| price / 1_000_000.0;
+
+    Cost {
+        input: (tokens.prompt - tokens.cached) as f64 * per_token(pricing.input),
+        cached_input: tokens.cached as f64 * per_token(pricing.cached_input),
+        output: (tokens.output + tokens.thoughts) as f64 * per_token(pricing.output),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct TokenUsage {
+    prompt: u64,
+    cached: u64,
+    output: u64,
+    thoughts: u64,
+}
+
+impl From<&UsageMetadata> for TokenUsage {
+    fn from(usage: &UsageMetadata) -> Self {
+        let count = |value: Option<i32>| value.unwrap_or(0).max(0) as u64;
+        let prompt = count(usage.prompt_token_count);
+
+        Self {
+            prompt,
+            cached: count(usage.cached_content_token_count).min(prompt),
+            output: count(usage.candidates_token_count),
+            thoughts: count(usage.thoughts_token_count),
+        }
+    }
+}
+
+/// The usage of one call, as stored by a `UsageTracker`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub model: String,
+    /// The feature the call is attributed to, see `UsageTracker::labeled`.
+    pub label: Option<String>,
+    pub prompt_tokens: u64,
+    pub cached_tokens: u64,
+    pub output_tokens: u64,
+    pub thoughts_tokens: u64,
+    /// The estimated cost in USD, `None` when the model has no known pricing.
+    pub cost: Option<f64>,
+}
+
+/// Aggregated usage of one label and model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageRow {
+    pub label: Option<String>,
+    pub model: String,
+    pub calls: usize,
+    pub prompt_tokens: u64,
+    pub cached_tokens: u64,
+    pub output_tokens: u64,
+    pub thoughts_tokens: u64,
+    pub cost: f64,
+    /// Calls whose cost could not be estimated.
+    pub unpriced_calls: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageSummary {
+    pub rows: Vec<UsageRow>,
+}
+
+impl UsageSummary {
+    pub fn from_records(records: &[UsageRecord]) -> Self {
+        let mut rows: BTreeMap<(Option<String>, String), UsageRow> = BTreeMap::new();
+
+        for record in records {
+            let row = rows
+                .entry((record.label.clone(), record.model.clone()))
+                .or_insert_with(|| UsageRow {
+                    label: record.label.clone(),
+                    model: record.model.clone(),
+                    ..Default::default()
+                });
+            row.calls += 1;
+            row.prompt_tokens += record.prompt_tokens;
+            row.cached_tokens += record.cached_tokens;
+            row.output_tokens += record.output_tokens;
+            row.thoughts_tokens += record.thoughts_tokens;
+            match record.cost {
+                Some(cost) => row.cost += cost,
+                None => row.unpriced_calls += 1,
+            }
+        }
+
+        Self {
+            rows: rows.into_values().collect(),
+        }
+    }
+
+    pub fn total_cost(&self) -> f64 {
+        self.rows.iter().map(|row| row.cost).sum()
+    }
+
+    /// Prints one line per label and model, followed by the total.
+    pub fn print(&self) {
+        for row in &self.rows {
+            println!(
+                r#"
+    Label: {}
+    Model: {}
+    Calls: {}
+    Prompt Tokens: {} ({} cached)
+    Output Tokens: {} ({} thinking)
+    Estimated Cost: ${:.6}{}"#,
+                row.label.as_deref().unwrap_or("-"),
+                row.model,
+                row.calls,
+                row.prompt_tokens,
+                row.cached_tokens,
+                row.output_tokens,
+                row.thoughts_tokens,
+                row.cost,
+                if row.unpriced_calls > 0 {
+                    format!(" ({} calls without pricing)", row.unpriced_calls)
+                } else {
+                    String::new()
+                }
+            );
+        }
+        println!("\nTotal Estimated Cost: ${:.6}", self.total_cost());
+    }
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    records: Vec<UsageRecord>,
+    pricing: HashMap<Model, Pricing>,
+    log: Option<PathBuf>,
+}
+
+/// Collects the usage metadata of the calls made through a `Client`.
+///
+/// Clones share the same records, so a tracker can be labeled per feature with
+/// `labeled` and attached to clients with `Client::with_usage_tracker`.
+#[derive(Debug, Clone, Default)]
+pub struct UsageTracker {
+    state: Arc<Mutex<TrackerState>>,
+    label: Option<String>,
+}
+
+impl UsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also appends every record as a JSON line to `path`, e.g. for `gems usage`.
+    pub fn with_log(self, path: impl Into<PathBuf>) -> Self {
+        self.state.lock().unwrap().log = Some(path.into());
+        self
+    }
+
+    /// Returns a tracker sharing these records which attributes its calls to `label`.
+    pub fn labeled(&self, label: &str) -> Self {
+        Self {
+            state: self.state.clone(),
+            label: Some(label.to_string()),
+        }
+    }
+
+    /// Overrides the pricing of a model, e.g. for custom or tuned models.
+    pub fn set_pricing(&self, model: Model, pricing: Pricing) {
+        self.state.lock().unwrap().pricing.insert(model, pricing);
+    }
+
+    pub fn record(&self, model: &Model, usage: &UsageMetadata) -> Result<UsageRecord> {
+        let mut state = self.state.lock().unwrap();
+        let tokens = TokenUsage::from(usage);
+        let pricing = state
+            .pricing
+            .get(model)
+            .copied()
+            .or_else(|| Pricing::for_model(model));
+
+        let record = UsageRecord {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            model: model.to_string(),
+            label: self.label.clone(),
+            prompt_tokens: tokens.prompt,
+            cached_tokens: tokens.cached,
+            output_tokens: tokens.output,
+            thoughts_tokens: tokens.thoughts,
+            cost: pricing.map(|pricing| estimate(&pricing, usage).total()),
+        };
+
+        if let Some(path) = &state.log {
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        }
+        state.records.push(record.clone());
+        Ok(record)
+    }
+
+    pub fn records(&self) -> Vec<UsageRecord> {
+        self.state.lock().unwrap().records.clone()
+    }
+
+    pub fn summary(&self) -> UsageSummary {
+        UsageSummary::from_records(&self.state.lock().unwrap().records)
+    }
+}
+
+/// Reads the records appended to a usage log by `UsageTracker::with_log`.
+pub fn load_log(path: impl AsRef<Path>) -> Result<Vec<UsageRecord>> {
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
@@ -1,30 +1,111 @@
-use crate::client::Client;
+use crate::client::{Client, Transport};
+use crate::embed::cache::cache_key;
 use crate::messages::Message;
 use crate::models::Model;
+use crate::openai;
+use crate::ratelimit::estimate_tokens;
 use crate::requests::Content;
 use crate::requests::GeminiEmbedRequest;
 use crate::requests::GeminiEmbedRequests;
+use crate::requests::TaskType;
 use crate::responses::BatchEmbedContentsResponse;
 use crate::responses::EmbedContentResponse;
+use crate::responses::Embedding as ResEmbedding;
 use crate::traits::CTrait;
-use anyhow::Result;
+use crate::utils::error_for_status;
+use anyhow::{anyhow, Result};
 use derive_builder::Builder;
+use futures_util::stream::{self, StreamExt};
 use reqwest::Method;
+use std::fmt;
+use std::ops::Range;
+
+pub mod cache;
+pub mod index;
+
+/// The maximum number of items the API accepts in one `batchEmbedContents` call.
+pub const MAX_BATCH_SIZE: usize = 100;
+
+const DEFAULT_CONCURRENCY: usize = 4;
 
 #[derive(Builder, Default, Clone)]
 #[builder(setter(into), default)]
 pub struct Embedding {
-    pub model: Model,
+    #[builder(setter(into, strip_option), default)]
+    pub model: Option<Model>,
     pub input: Message,
+    #[builder(setter(into, strip_option), default)]
+    pub task_type: Option<TaskType>,
+    /// Document title, only used with `TaskType::RetrievalDocument`.
+    #[builder(setter(into, strip_option), default)]
+    pub title: Option<String>,
+    #[builder(setter(into, strip_option), default)]
+    pub output_dimensionality: Option<u32>,
 }
 
 #[derive(Builder, Default, Clone)]
 #[builder(setter(into), default)]
 pub struct BatchEmbedding {
-    pub model: Model,
+    #[builder(setter(into, strip_option), default)]
+    pub model: Option<Model>,
     pub input: Vec<Message>,
+    #[builder(setter(into, strip_option), default)]
+    pub task_type: Option<TaskType>,
+    /// Title applied to every document, only used with `TaskType::RetrievalDocument`.
+    #[builder(setter(into, strip_option), default)]
+    pub title: Option<String>,
+    #[builder(setter(into, strip_option), default)]
+    pub output_dimensionality: Option<u32>,
+    /// Number of items sent per API call, capped at `MAX_BATCH_SIZE`.
+    #[builder(setter(into, strip_option), default)]
+    pub chunk_size: Option<usize>,
+    /// Number of chunks sent concurrently, 4 by default.
+    #[builder(setter(into, strip_option), default)]
+    pub concurrency: Option<usize>,
+}
+
+/// Error returned when some chunks of a batch embedding failed.
+///
+/// Embeddings of the successful chunks are kept at the position of their input, so only
+/// the failed ranges need to be retried. Obtain it with `anyhow::Error::downcast_ref`.
+#[derive(Debug)]
+pub struct BatchEmbedError {
+    /// One slot per input, `None` for inputs of failed chunks.
+    pub embeddings: Vec<Option<ResEmbedding>>,
+    /// The failed chunks, in input order.
+    pub failures: Vec<ChunkFailure>,
+}
+
+/// A chunk of a batch embedding that could not be embedded.
+#[derive(Debug)]
+pub struct ChunkFailure {
+    /// The positions of the chunk inputs in the batch.
+    pub range: Range<usize>,
+    pub error: anyhow::Error,
+}
+
+impl fmt::Display for BatchEmbedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failed: usize = self.failures.iter().map(|f| f.range.len()).sum();
+        write!(
+            f,
+            "Failed to embed {} of {} inputs",
+            failed,
+            self.embeddings.len()
+        )?;
+        if let Some(first) = self.failures.first() {
+            write!(
+                f,
+                " (inputs {}..{}: {})",
+                first.range.start, first.range.end, first.error
+            )?;
+        }
+        Ok(())
+    }
 }
 
+impl std::error::Error for BatchEmbedError {}
+
 #[derive(Clone)]
 pub struct Embeddings {
     pub client: Client,
@@ -32,42 +113,203 @@ pub struct Embeddings {
 
 impl Embeddings {
     pub async fn create(&self, params: Embedding) -> Result<EmbedContentResponse> {
+        let model = params.model.unwrap_or_else(|| self.client.get_model());
+
         let request_body = GeminiEmbedRequest {
-            model: format!("models/{}", params.model.to_string()),
+            model: model.resource_name(),
             content: Content {
                 parts: vec![params.input.to_part()],
+                role: None,
             },
+            task_type: params.task_type,
+            title: params.title,
+            output_dimensionality: params.output_dimensionality,
+        };
+
+        let cache = self.client.embedding_cache();
+        let key = match cache {
+            Some(cache) => {
+                let key = cache_key(&request_body)?;
+                if let Some(embedding) = cache.get(&key)? {
+                    return Ok(EmbedContentResponse {
+                        embedding: Some(embedding),
+                    });
+                }
+                Some(key)
+            }
+            None => None,
         };
 
-        let req = self
-            .client
-            .request(Method::POST, "embedContent")?
-            .json(&request_body);
+        self.client.ensure_supports(&model, "embedContent").await?;
+        self.client
+            .throttle_tokens(&model, estimate_tokens([&request_body.content]))
+            .await?;
+
+        let json = match self.client.transport() {
+            Transport::Gemini => {
+                let req = self
+                    .client
+                    .request_with_model(Method::POST, &model, "embedContent")?
+                    .json(&request_body);
+
+                let res = error_for_status(self.client.send(req).await?).await?;
+                res.json::<EmbedContentResponse>().await?
+            }
+            Transport::OpenAi => EmbedContentResponse {
+                embedding: openai::embed(&self.client, &model, &[request_body])
+                    .await?
+                    .pop(),
+            },
+        };
 
-        let res = req.send().await?;
-        Ok(res.json().await?)
+        if let (Some(cache), Some(key), Some(embedding)) = (cache, key, &json.embedding) {
+            cache.put(&key, embedding)?;
+        }
+        Ok(json)
     }
 
+    /// Embeds many inputs, splitting them into chunks of at most `MAX_BATCH_SIZE` items.
+    ///
+    /// Chunks run concurrently and the embeddings are returned in input order. If any chunk
+    /// fails, the error is a `BatchEmbedError` holding the embeddings of the other chunks.
+    /// With an embedding cache, only the inputs missing from the cache are sent.
     pub async fn batch(&self, params: BatchEmbedding) -> Result<BatchEmbedContentsResponse> {
+        let model = params.model.unwrap_or_else(|| self.client.get_model());
+
         let requests = params
             .input
             .into_iter()
             .map(|message| GeminiEmbedRequest {
-                model: format!("models/{}", params.model.to_string()),
+                model: model.resource_name(),
                 content: Content {
                     parts: vec![message.to_part()],
+                    role: None,
                 },
+                task_type: params.task_type,
+                title: params.title.clone(),
+                output_dimensionality: params.output_dimensionality,
             })
             .collect::<Vec<GeminiEmbedRequest>>();
 
-        let request_body = GeminiEmbedRequests { requests };
+        self.client.ensure_supports(&model, "embedContent").await?;
+
+        let chunk_size = params
+            .chunk_size
+            .unwrap_or(MAX_BATCH_SIZE)
+            .clamp(1, MAX_BATCH_SIZE);
+        let concurrency = params.concurrency.unwrap_or(DEFAULT_CONCURRENCY).max(1);
+        let total = requests.len();
+
+        let mut chunks = Vec::new();
+        let mut requests = requests.into_iter();
+        for start in (0..total).step_by(chunk_size) {
+            let chunk: Vec<GeminiEmbedRequest> = requests.by_ref().take(chunk_size).collect();
+            chunks.push((start..start + chunk.len(), chunk));
+        }
+
+        let results = stream::iter(chunks)
+            .map(|(range, requests)| {
+                let model = &model;
+                async move { (range, self.cached_chunk(model, requests).await) }
+            })
+            .buffered(concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut embeddings = Vec::with_capacity(total);
+        let mut failures = Vec::new();
+        for (range, result) in results {
+            match result {
+                Ok(chunk) => embeddings.extend(chunk.into_iter().map(Some)),
+                Err(error) => {
+                    embeddings.extend(range.clone().map(|_| None));
+                    failures.push(ChunkFailure { range, error });
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(BatchEmbedContentsResponse {
+                embeddings: Some(embeddings.into_iter().flatten().collect()),
+            })
+        } else {
+            Err(BatchEmbedError {
+                embeddings,
+                failures,
+            }
+            .into())
+        }
+    }
+
+    /// Embeds a chunk, serving cached inputs from the cache and storing the others.
+    async fn cached_chunk(
+        &self,
+        model: &Model,
+        requests: Vec<GeminiEmbedRequest>,
+    ) -> Result<Vec<ResEmbedding>> {
+        let Some(cache) = self.client.embedding_cache() else {
+            return self.batch_chunk(model, requests).await;
+        };
+
+        let mut keys = Vec::with_capacity(requests.len());
+        let mut embeddings = Vec::with_capacity(requests.len());
+        let mut misses = Vec::new();
+        for request in requests {
+            let key = cache_key(&request)?;
+            let cached = cache.get(&key)?;
+            if cached.is_none() {
+                misses.push(request);
+            }
+            keys.push(key);
+            embeddings.push(cached);
+        }
 
-        let req = self
-            .client
-            .request(Method::POST, "batchEmbedContents")?
-            .json(&request_body);
+        if !misses.is_empty() {
+            let mut fetched = self.batch_chunk(model, misses).await?.into_iter();
+            for (key, slot) in keys.iter().zip(embeddings.iter_mut()) {
+                if slot.is_none() {
+                    let embedding = fetched.next().unwrap_or_default();
+                    cache.put(key, &embedding)?;
+                    *slot = Some(embedding);
+                }
+            }
+        }
+
+        Ok(embeddings.into_iter().flatten().collect())
+    }
+
+    async fn batch_chunk(
+        &self,
+        model: &Model,
+        requests: Vec<GeminiEmbedRequest>,
+    ) -> Result<Vec<ResEmbedding>> {
+        let expected = requests.len();
+        let tokens = estimate_tokens(requests.iter().map(|request| &request.content));
+        self.client.throttle_tokens(model, tokens).await?;
+
+        let embeddings = match self.client.transport() {
+            Transport::Gemini => {
+                let request_body = GeminiEmbedRequests { requests };
+                let req = self
+                    .client
+                    .request_with_model(Method::POST, model, "batchEmbedContents")?
+                    .json(&request_body);
+
+                let res = error_for_status(self.client.send(req).await?).await?;
+                let json: BatchEmbedContentsResponse = res.json().await?;
+                json.embeddings.unwrap_or_default()
+            }
+            Transport::OpenAi => openai::embed(&self.client, model, &requests).await?,
+        };
 
-        let res = req.send().await?;
-        Ok(res.json().await?)
+        if embeddings.len() == expected {
+            Ok(embeddings)
+        } else {
+            Err(anyhow!(
+                "Expected {} embeddings but received {}",
+                expected,
+                embeddings.len()
+            ))
+        }
     }
 }
@@ -0,0 +1,137 @@
+use crate::requests::GeminiEmbedRequest;
+use crate::responses::Embedding;
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Numbers the temporary files of `DiskCache::put` within the process.
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A store for embeddings, consulted by `Embeddings::create` and `Embeddings::batch`
+/// before calling the API.
+///
+/// Keys are produced by `cache_key` and already cover the model, task type, title,
+/// output dimensionality and content of the request.
+pub trait EmbeddingCache: Debug + Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<Embedding>>;
+    fn put(&self, key: &str, embedding: &Embedding) -> Result<()>;
+}
+
+/// Returns the hex SHA-256 digest identifying the embedding of `request`.
+pub fn cache_key(request: &GeminiEmbedRequest) -> Result<String> {
+    let digest = Sha256::digest(serde_json::to_vec(request)?);
+    Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// An in-memory cache evicting the least recently used embedding once full.
+#[derive(Debug)]
+pub struct MemoryCache {
+    capacity: usize,
+    state: Mutex<MemoryState>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    tick: u64,
+    entries: HashMap<String, (Embedding, u64)>,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: Mutex::new(MemoryState::default()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl EmbeddingCache for MemoryCache {
+    fn get(&self, key: &str) -> Result<Option<Embedding>> {
+        let mut state = self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+
+        Ok(state.entries.get_mut(key).map(|(embedding, used)| {
+            *used = tick;
+            embedding.clone()
+        }))
+    }
+
+    fn put(&self, key: &str, embedding: &Embedding) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+
+        if !state.entries.contains_key(key) && state.entries.len() >= self.capacity {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        state
+            .entries
+            .insert(key.to_string(), (embedding.clone(), tick));
+        Ok(())
+    }
+}
+
+/// A cache storing one JSON file per embedding in a directory, kept across runs.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    /// Opens the cache in `dir`, creating the directory if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+impl EmbeddingCache for DiskCache {
+    fn get(&self, key: &str) -> Result<Option<Embedding>> {
+        match fs::read(self.path(key)) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn put(&self, key: &str, embedding: &Embedding) -> Result<()> {
+        // Write then rename so concurrent readers never see a partial file, with a temporary
+        // file of its own for every put so that concurrent puts of a key never share one.
+        let path = self.path(key);
+        let tmp = path.with_extension(format!(
+            "{}.{}.tmp",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&tmp, serde_json::to_vec(embedding)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+use crate::responses::{BatchEmbedContentsResponse, Embedding};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+
+/// The measure used to rank entries against a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Metric {
+    /// Cosine similarity, higher is more similar.
+    #[default]
+    Cosine,
+    /// Dot product, higher is more similar.
+    DotProduct,
+    /// Euclidean distance, lower is more similar.
+    Euclidean,
+}
+
+/// An embedding stored in the index with its id and metadata.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub id: String,
+    pub values: Vec<f64>,
+    #[serde(default)]
+    pub metadata: Value,
+}
+
+/// An entry matching a query, with its score under the search metric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub id: String,
+    pub score: f64,
+    pub metadata: Value,
+}
+
+/// An in-memory vector store with brute-force top-k search.
+///
+/// Meant for small corpora, e.g. a few thousand documents embedded with
+/// `Embeddings::batch`, and persisted as JSON between runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VectorIndex {
+    entries: Vec<Entry>,
+}
+
+impl VectorIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an embedding, replacing any entry with the same id.
+    ///
+    /// All embeddings of an index must have the same number of dimensions.
+    pub fn add(&mut self, id: &str, embedding: Embedding, metadata: Value) -> Result<()> {
+        if let Some(dimensions) = self.dimensions() {
+            if embedding.values.len() != dimensions {
+                return Err(anyhow!(
+                    "Embedding {} has {} dimensions, expected {}",
+                    id,
+                    embedding.values.len(),
+                    dimensions
+                ));
+            }
+        }
+
+        let entry = Entry {
+            id: id.to_string(),
+            values: embedding.values,
+            metadata,
+        };
+        match self.entries.iter_mut().find(|e| e.id == id) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+        Ok(())
+    }
+
+    /// Adds the embeddings of a batch response, pairing them in order with `items`.
+    pub fn add_batch<I>(&mut self, items: I, response: BatchEmbedContentsResponse) -> Result<()>
+    where
+        I: IntoIterator<Item = (String, Value)>,
+    {
+        let items: Vec<(String, Value)> = items.into_iter().collect();
+        let embeddings = response.embeddings.unwrap_or_default();
+
+        if items.len() != embeddings.len() {
+            return Err(anyhow!(
+                "Got {} ids for {} embeddings",
+                items.len(),
+                embeddings.len()
+            ));
+        }
+
+        for ((id, metadata), embedding) in items.into_iter().zip(embeddings) {
+            self.add(&id, embedding, metadata)?;
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<Entry> {
+        let position = self.entries.iter().position(|e| e.id == id)?;
+        Some(self.entries.remove(position))
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The number of dimensions of the stored embeddings, `None` while empty.
+    pub fn dimensions(&self) -> Option<usize> {
+        self.entries.first().map(|e| e.values.len())
+    }
+
+    /// Returns the `k` entries closest to `query`, best match first.
+    pub fn search(&self, query: &[f64], k: usize, metric: Metric) -> Vec<SearchResult> {
+        let mut results: Vec<SearchResult> = self
+            .entries
+            .iter()
+            .filter(|e| e.values.len() == query.len())
+            .map(|e| SearchResult {
+                id: e.id.clone(),
+                score: match metric {
+                    Metric::Cosine => cosine_similarity(query, &e.values),
+                    Metric::DotProduct => dot_product(query, &e.values),
+                    Metric::Euclidean => euclidean_distance(query, &e.values),
+                },
+                metadata: e.metadata.clone(),
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            let order = b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal);
+            match metric {
+                Metric::Euclidean => order.reverse(),
+                _ => order,
+            }
+        });
+        results.truncate(k);
+        results
+    }
+
+    /// Writes the index to `path` as JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    /// Reads an index previously written with `save`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+}
+
+pub fn dot_product(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Cosine similarity of two vectors, `0.0` if either has no magnitude.
+pub fn cosine_similarity(a: &[f64], b: &[f64]) -> f64 {
+    let norms = dot_product(a, a).sqrt() * dot_product(b, b).sqrt();
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot_product(a, b) / norms
+    }
+}
+
+pub fn euclidean_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Scales `values` to unit length in place, leaving zero vectors untouched.
+pub fn normalize(values: &mut [f64]) {
+    let norm = dot_product(values, values).sqrt();
+    if norm > 0.0 {
+        values.iter_mut().for_each(|v| *v /= norm);
+    }
+}
@@ -5,7 +5,7 @@ use crate::requests::GenerationConfig;
 use crate::requests::{Content, GeminiRequest};
 use crate::responses::ImagenResponse;
 use crate::traits::CTrait;
-use crate::utils::extract_image_or_text;
+use crate::utils::{error_for_status, extract_image_or_text};
 use anyhow::{anyhow, Result};
 use derive_builder::Builder;
 use reqwest::Method;
@@ -18,7 +18,8 @@ pub struct Images {
 #[derive(Builder, Clone)]
 #[builder(setter(into))]
 pub struct ImageGen {
-    pub model: Model,
+    #[builder(setter(into, strip_option), default)]
+    pub model: Option<Model>,
     pub input: Message,
     #[builder(setter(into, strip_option), default)]
     pub system: Option<Vec<Message>>,
@@ -28,28 +29,42 @@ impl Images {
     pub async fn generate(&self, params: ImageGen) -> Result<Vec<u8>> {
         let content = Content {
             parts: vec![params.input.to_part()],
+            role: None,
         };
 
         let system_instruction = params.system.as_ref().map(|messages| Content {
             parts: messages.iter().map(|msg| msg.to_part()).collect(),
+            role: None,
         });
 
+        let model = params.model.unwrap_or_else(|| self.client.get_model());
+
         let request_body = GeminiRequest {
-            model: params.model.to_string(),
+            model: model.to_string(),
             contents: vec![content],
             system_instruction,
             config: Some(GenerationConfig {
                 response_modalities: vec!["Text".into(), "Image".into()],
+                ..Default::default()
             }),
+            tools: None,
+            cached_content: None,
         };
 
+        self.client
+            .ensure_supports(&model, "generateContent")
+            .await?;
+        self.client.throttle(&model, &request_body).await?;
+
         let req = self
             .client
-            .request(Method::POST, "generateContent")?
+            .request_with_model(Method::POST, &model, "generateContent")?
             .json(&request_body);
 
-        let res = req.send().await?;
+        let res = error_for_status(self.client.send(req).await?).await?;
         let json: ImagenResponse = res.json().await?;
+        self.client
+            .record_usage(&model, json.usage_metadata.as_ref())?;
 
         let parts = json
             .candidates
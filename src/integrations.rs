@@ -0,0 +1,4 @@
+//! Ready-made integrations with web frameworks, each behind its own feature.
+
+#[cfg(feature = "axum")]
+pub mod axum;
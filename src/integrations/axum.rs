@@ -0,0 +1,397 @@
+//! An axum `Router` exposing the sub-clients as JSON endpoints.
+//!
+//! | Method | Path                | Body            | Response                     |
+//! |--------|---------------------|-----------------|------------------------------|
+//! | POST   | `/chat`             | `ChatRequest`   | `GeminiResponse`             |
+//! | POST   | `/chat/stream`      | `ChatRequest`   | SSE of `GeminiResponse`      |
+//! | POST   | `/tokens/count`     | `ChatRequest`   | `CountTokensResponse`        |
+//! | POST   | `/embeddings`       | `EmbedRequest`  | `EmbedContentResponse`       |
+//! | POST   | `/embeddings/batch` | `EmbedRequest`  | `BatchEmbedContentsResponse` |
+//! | POST   | `/vision`           | `VisionRequest` | `TextResponse`               |
+//! | POST   | `/images`           | `MediaRequest`  | `MediaResponse`              |
+//! | POST   | `/videos`           | `MediaRequest`  | `MediaResponse`              |
+//! | POST   | `/tts`              | `MediaRequest`  | `MediaResponse`              |
+//! | GET    | `/models`           |                 | `ModelsResponse`             |
+//! | GET    | `/models/{model}`   |                 | `ModelInfo`                  |
+//!
+//! Requests may carry their own API key in the `x-goog-api-key` header or as an
+//! `Authorization: Bearer` token, which then replaces the key of the shared `Client`.
+
+use crate::chat::Chat;
+use crate::embed::{BatchEmbeddingBuilder, EmbeddingBuilder};
+use crate::imagen::ImageGenBuilder;
+use crate::messages::Message;
+use crate::models::Model;
+use crate::requests::{TaskType, Tool};
+use crate::responses::{
+    BatchEmbedContentsResponse, CountTokensResponse, EmbedContentResponse, GeminiResponse,
+    ModelInfo, ModelsResponse,
+};
+use crate::traits::CTrait;
+use crate::tts::TtsGenBuilder;
+use crate::utils::HttpError;
+use crate::vidgen::VideoGenBuilder;
+use crate::vision::VisionBuilder;
+use crate::Client;
+use ::axum::extract::{FromRequestParts, Path};
+use ::axum::http::header::AUTHORIZATION;
+use ::axum::http::request::Parts;
+use ::axum::http::StatusCode;
+use ::axum::response::sse::{Event, KeepAlive, Sse};
+use ::axum::response::{IntoResponse, Response};
+use ::axum::routing::{get, post};
+use ::axum::{Json, Router};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::convert::Infallible;
+
+/// Returns the endpoints with `client` as shared state.
+pub fn router(client: Client) -> Router {
+    routes().with_state(client)
+}
+
+/// Returns the endpoints without state, to be merged into an app whose state is a `Client`.
+pub fn routes() -> Router<Client> {
+    Router::new()
+        .route("/chat", post(chat))
+        .route("/chat/stream", post(chat_stream))
+        .route("/tokens/count", post(count_tokens))
+        .route("/embeddings", post(embed))
+        .route("/embeddings/batch", post(embed_batch))
+        .route("/vision", post(vision))
+        .route("/images", post(images))
+        .route("/videos", post(videos))
+        .route("/tts", post(tts))
+        .route("/models", get(models))
+        .route("/models/{model}", get(model_info))
+}
+
+/// An error answered as `{"error": {"code", "message"}}`.
+#[derive(Debug)]
+pub struct Error {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl Error {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for Error {
+    fn from(error: anyhow::Error) -> Self {
+        Self::new(status_of(&error), error.to_string())
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let body = json!({ "error": { "code": self.status.as_u16(), "message": self.message } });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+/// The status to answer with for an error of the Gemini API or of the HTTP client.
+///
+/// Client errors of the API are passed through, server errors become `502 Bad Gateway`
+/// (or `503`/`504` as returned) and anything else is an internal error.
+pub fn status_of(error: &anyhow::Error) -> StatusCode {
+    for cause in error.chain() {
+        if let Some(http) = cause.downcast_ref::<HttpError>() {
+            return match http.status {
+                StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => http.status,
+                status if status.is_client_error() => status,
+                _ => StatusCode::BAD_GATEWAY,
+            };
+        }
+        if let Some(reqwest) = cause.downcast_ref::<reqwest::Error>() {
+            return if reqwest.is_timeout() {
+                StatusCode::GATEWAY_TIMEOUT
+            } else if reqwest.is_decode() || reqwest.is_connect() {
+                StatusCode::BAD_GATEWAY
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+fn bad_request(error: impl ToString) -> Error {
+    Error::new(StatusCode::BAD_REQUEST, error.to_string())
+}
+
+/// The shared `Client`, or a copy of it calling the API with the key of the request.
+///
+/// Clients authenticating with `CBuilder::credentials`, such as on Vertex AI, never send
+/// API keys, so they are used as is.
+pub struct RequestClient(pub Client);
+
+impl FromRequestParts<Client> for RequestClient {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, client: &Client) -> Result<Self, Error> {
+        if client.has_credentials() {
+            return Ok(Self(client.clone()));
+        }
+        let header = |name| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        let key = header("x-goog-api-key").or_else(|| {
+            header(AUTHORIZATION.as_str()).and_then(|value| value.strip_prefix("Bearer "))
+        });
+
+        match key.map(str::trim).filter(|key| !key.is_empty()) {
+            Some(key) => Ok(Self(client.with_api_key(key))),
+            None if client.get_api_key().is_some_and(|key| !key.is_empty()) => {
+                Ok(Self(client.clone()))
+            }
+            None => Err(Error::new(StatusCode::UNAUTHORIZED, "Missing API key")),
+        }
+    }
+}
+
+/// The body of the chat, streaming and token counting endpoints.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatRequest {
+    pub model: Option<Model>,
+    pub messages: Vec<Message>,
+    pub system: Option<Vec<Message>>,
+    pub tools: Option<Vec<Tool>>,
+    pub cached_content: Option<String>,
+    pub thinking_budget: Option<i32>,
+    pub include_thoughts: Option<bool>,
+}
+
+impl From<ChatRequest> for Chat {
+    fn from(request: ChatRequest) -> Self {
+        Chat {
+            model: request.model,
+            messages: request.messages,
+            system: request.system,
+            tools: request.tools,
+            cached_content: request.cached_content,
+            thinking_budget: request.thinking_budget,
+            include_thoughts: request.include_thoughts,
+        }
+    }
+}
+
+/// The body of the embedding endpoints; `input` is a list for `/embeddings/batch`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbedRequest<T> {
+    pub model: Option<Model>,
+    pub input: T,
+    pub task_type: Option<TaskType>,
+    pub title: Option<String>,
+    pub output_dimensionality: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VisionRequest {
+    pub model: Option<Model>,
+    pub input: Message,
+    /// A message whose content is the image.
+    pub image: Message,
+    pub system: Option<Vec<Message>>,
+}
+
+/// The body of the image, video and speech generation endpoints.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaRequest {
+    pub model: Option<Model>,
+    pub input: Message,
+    pub system: Option<Vec<Message>>,
+    /// The prebuilt voice of `/tts`.
+    pub voice: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextResponse {
+    pub text: String,
+}
+
+/// Generated media as base64.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaResponse {
+    pub mime_type: String,
+    pub data: String,
+}
+
+impl MediaResponse {
+    fn new(mime_type: &str, bytes: &[u8]) -> Json<Self> {
+        Json(Self {
+            mime_type: mime_type.to_string(),
+            data: STANDARD.encode(bytes),
+        })
+    }
+}
+
+async fn chat(
+    RequestClient(client): RequestClient,
+    Json(request): Json<ChatRequest>,
+) -> Result<Json<GeminiResponse>, Error> {
+    Ok(Json(client.chat().generate_response(request.into()).await?))
+}
+
+async fn chat_stream(
+    RequestClient(client): RequestClient,
+    Json(request): Json<ChatRequest>,
+) -> Result<impl IntoResponse, Error> {
+    let chunks = client.chat().stream(request.into()).await?;
+    let events = chunks.map(|chunk| {
+        let event = match chunk {
+            Ok(response) => Event::default()
+                .json_data(response)
+                .unwrap_or_else(|e| error_event(&e.into())),
+            Err(error) => error_event(&error),
+        };
+        Ok::<_, Infallible>(event)
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+fn error_event(error: &anyhow::Error) -> Event {
+    let body = json!({ "code": status_of(error).as_u16(), "message": error.to_string() });
+    Event::default().event("error").data(body.to_string())
+}
+
+async fn count_tokens(
+    RequestClient(client): RequestClient,
+    Json(request): Json<ChatRequest>,
+) -> Result<Json<CountTokensResponse>, Error> {
+    Ok(Json(client.tokens().count_chat(&request.into()).await?))
+}
+
+async fn embed(
+    RequestClient(client): RequestClient,
+    Json(request): Json<EmbedRequest<Message>>,
+) -> Result<Json<EmbedContentResponse>, Error> {
+    let mut builder = EmbeddingBuilder::default();
+    builder
+        .model(request.model.unwrap_or(Model::Embedding))
+        .input(request.input);
+    if let Some(task_type) = request.task_type {
+        builder.task_type(task_type);
+    }
+    if let Some(title) = request.title {
+        builder.title(title);
+    }
+    if let Some(dimensions) = request.output_dimensionality {
+        builder.output_dimensionality(dimensions);
+    }
+    let params = builder.build().map_err(bad_request)?;
+    Ok(Json(client.embeddings().create(params).await?))
+}
+
+async fn embed_batch(
+    RequestClient(client): RequestClient,
+    Json(request): Json<EmbedRequest<Vec<Message>>>,
+) -> Result<Json<BatchEmbedContentsResponse>, Error> {
+    let mut builder = BatchEmbeddingBuilder::default();
+    builder
+        .model(request.model.unwrap_or(Model::Embedding))
+        .input(request.input);
+    if let Some(task_type) = request.task_type {
+        builder.task_type(task_type);
+    }
+    if let Some(title) = request.title {
+        builder.title(title);
+    }
+    if let Some(dimensions) = request.output_dimensionality {
+        builder.output_dimensionality(dimensions);
+    }
+    let params = builder.build().map_err(bad_request)?;
+    Ok(Json(client.embeddings().batch(params).await?))
+}
+
+async fn vision(
+    RequestClient(client): RequestClient,
+    Json(request): Json<VisionRequest>,
+) -> Result<Json<TextResponse>, Error> {
+    let mut builder = VisionBuilder::default();
+    builder.input(request.input).image(request.image);
+    if let Some(model) = request.model {
+        builder.model(model);
+    }
+    if let Some(system) = request.system {
+        builder.system(system);
+    }
+    let params = builder.build().map_err(bad_request)?;
+    let text = client.vision().generate(params).await?;
+    Ok(Json(TextResponse { text }))
+}
+
+async fn images(
+    RequestClient(client): RequestClient,
+    Json(request): Json<MediaRequest>,
+) -> Result<Json<MediaResponse>, Error> {
+    let mut builder = ImageGenBuilder::default();
+    builder
+        .model(request.model.unwrap_or(Model::FlashExpImage))
+        .input(request.input);
+    if let Some(system) = request.system {
+        builder.system(system);
+    }
+    let params = builder.build().map_err(bad_request)?;
+    let image = client.images().generate(params).await?;
+    Ok(MediaResponse::new("image/png", &image))
+}
+
+async fn videos(
+    RequestClient(client): RequestClient,
+    Json(request): Json<MediaRequest>,
+) -> Result<Json<MediaResponse>, Error> {
+    let mut builder = VideoGenBuilder::default();
+    builder
+        .model(request.model.unwrap_or(Model::Veo2))
+        .input(request.input);
+    if let Some(system) = request.system {
+        builder.system(system);
+    }
+    let params = builder.build().map_err(bad_request)?;
+    let video = client.videos().generate(params).await?;
+    Ok(MediaResponse::new("video/mp4", &video))
+}
+
+async fn tts(
+    RequestClient(client): RequestClient,
+    Json(request): Json<MediaRequest>,
+) -> Result<Json<MediaResponse>, Error> {
+    let mut builder = TtsGenBuilder::default();
+    builder
+        .model(request.model.unwrap_or(Model::Tts))
+        .input(request.input);
+    if let Some(system) = request.system {
+        builder.system(system);
+    }
+    if let Some(voice) = request.voice {
+        builder.voice(voice);
+    }
+    let params = builder.build().map_err(bad_request)?;
+    let audio = client.tts().generate(params).await?;
+    Ok(MediaResponse::new("audio/pcm;rate=24000", &audio))
+}
+
+async fn models(RequestClient(client): RequestClient) -> Result<Json<ModelsResponse>, Error> {
+    Ok(Json(client.models().list().await?))
+}
+
+async fn model_info(
+    RequestClient(client): RequestClient,
+    Path(model): Path<String>,
+) -> Result<Json<ModelInfo>, Error> {
+    let model = model.parse::<Model>().map_err(bad_request)?;
+    Ok(Json(client.models().info(&model).await?))
+}
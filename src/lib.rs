@@ -1,14 +1,24 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![doc = include_str!("../README.md")]
 
+pub mod auth;
 pub mod chat;
 pub mod client;
+pub mod cost;
 pub mod embed;
 pub mod imagen;
+#[cfg(feature = "axum")]
+pub mod integrations;
+pub mod live;
 pub mod messages;
 pub mod models;
+pub mod openai;
+pub mod rag;
+pub mod ratelimit;
 pub mod requests;
 pub mod responses;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod stream;
 pub mod tokens;
 pub mod traits;
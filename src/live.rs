@@ -0,0 +1,319 @@
+use crate::client::{Backend, Client, API_KEY_HEADER};
+use crate::messages::Message;
+use crate::models::Model;
+use crate::requests::{
+    CodeExecutionResult, Content, ExecutableCode, FunctionResponse, LiveClientContent,
+    LiveClientMessage, LiveGenerationConfig, LiveRealtimeInput, LiveSetup, LiveToolResponse, Part,
+    PrebuiltVoiceConfig, SpeechConfig, Tool, VoiceConfig,
+};
+use crate::responses::{FunctionCall, InlineData, LiveServerMessage};
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use derive_builder::Builder;
+use futures_util::{SinkExt, StreamExt};
+use reqwest::Url;
+use std::collections::VecDeque;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+#[derive(Clone)]
+pub struct Lives {
+    pub client: Client,
+}
+
+#[derive(Builder, Clone)]
+#[builder(setter(into))]
+pub struct Live {
+    pub model: Model,
+    /// Modalities the model answers with, `TEXT` or `AUDIO`.
+    #[builder(default = "vec![String::from(\"TEXT\")]")]
+    pub response_modalities: Vec<String>,
+    #[builder(setter(into, strip_option), default)]
+    pub system: Option<Vec<Message>>,
+    #[builder(setter(into, strip_option), default)]
+    pub tools: Option<Vec<Tool>>,
+    #[builder(setter(into, strip_option), default)]
+    pub voice: Option<String>,
+}
+
+/// An event received from the model during a Live API session.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiveEvent {
+    /// A chunk of generated text.
+    Text(String),
+    /// A chunk of generated PCM audio.
+    Audio(Vec<u8>),
+    /// Code the model runs with `Tool::code_execution`.
+    ExecutableCode(ExecutableCode),
+    /// The result of running `ExecutableCode`.
+    CodeExecutionResult(CodeExecutionResult),
+    /// The model asks the client to run one or more functions.
+    ToolCall(Vec<FunctionCall>),
+    /// Previously requested function calls that should no longer be run.
+    ToolCallCancellation(Vec<String>),
+    /// The user interrupted the model; buffered playback should be discarded.
+    Interrupted,
+    /// The model finished generating the current response.
+    GenerationComplete,
+    /// The model finished its turn and waits for more input.
+    TurnComplete,
+    /// The server will close the session soon, after the given time left.
+    GoAway(Option<String>),
+}
+
+/// An open bidirectional session with a Live API model.
+pub struct LiveSession {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    pending: VecDeque<LiveEvent>,
+}
+
+impl Lives {
+    /// Opens a `BidiGenerateContent` WebSocket session and waits for the setup to complete.
+    pub async fn connect(&self, params: Live) -> Result<LiveSession> {
+        self.client
+            .ensure_supports(&params.model, "bidiGenerateContent")
+            .await?;
+        self.client.throttle_tokens(&params.model, 0).await?;
+
+        if *self.client.backend() != Backend::GeminiApi {
+            return Err(anyhow!("The Live API is not supported on Vertex AI"));
+        }
+        let (api_key, in_query) = self.client.live_api_key()?;
+        let request = if in_query {
+            Url::parse_with_params(self.client.live_url(), &[("key", &api_key)])?
+                .as_str()
+                .into_client_request()?
+        } else {
+            let mut request = self.client.live_url().into_client_request()?;
+            request
+                .headers_mut()
+                .insert(API_KEY_HEADER, HeaderValue::from_str(&api_key)?);
+            request
+        };
+
+        let (socket, _) = connect_async(request)
+            .await
+            .map_err(|e| anyhow!("Failed to connect to the Live API: {}", e))?;
+
+        let mut session = LiveSession {
+            socket,
+            pending: VecDeque::new(),
+        };
+
+        let speech_config = params.voice.map(|voice_name| SpeechConfig {
+            voice_config: VoiceConfig {
+                prebuilt_voice_config: PrebuiltVoiceConfig { voice_name },
+            },
+        });
+
+        let system_instruction = params.system.as_ref().map(|messages| Content {
+            parts: messages.iter().map(|msg| msg.to_part()).collect(),
+            role: None,
+        });
+
+        session
+            .send(LiveClientMessage::Setup(LiveSetup {
+                model: params.model.resource_name(),
+                generation_config: Some(LiveGenerationConfig {
+                    response_modalities: params.response_modalities,
+                    speech_config,
+                }),
+                system_instruction,
+                tools: params.tools,
+            }))
+            .await?;
+
+        match session.receive_message().await? {
+            Some(message) if message.setup_complete.is_some() => Ok(session),
+            Some(_) => Err(anyhow!("Expected setupComplete from the Live API")),
+            None => Err(anyhow!("Live session closed during setup")),
+        }
+    }
+}
+
+impl LiveSession {
+    /// Sends a complete user turn and lets the model respond.
+    pub async fn send_text(&mut self, text: &str) -> Result<()> {
+        self.send(LiveClientMessage::ClientContent(LiveClientContent {
+            turns: vec![Content {
+                parts: vec![Part::text(text)],
+                role: None,
+            }],
+            turn_complete: true,
+        }))
+        .await
+    }
+
+    /// Sends text as realtime input, subject to voice activity detection like audio.
+    pub async fn send_realtime_text(&mut self, text: &str) -> Result<()> {
+        self.send(LiveClientMessage::RealtimeInput(LiveRealtimeInput {
+            text: Some(text.to_string()),
+            ..Default::default()
+        }))
+        .await
+    }
+
+    /// Sends a chunk of raw audio, e.g. with mime type `audio/pcm;rate=16000`.
+    pub async fn send_audio(&mut self, audio: &[u8], mime_type: &str) -> Result<()> {
+        self.send(LiveClientMessage::RealtimeInput(LiveRealtimeInput {
+            audio: Some(InlineData {
+                mime_type: mime_type.to_string(),
+                data: STANDARD.encode(audio),
+            }),
+            ..Default::default()
+        }))
+        .await
+    }
+
+    /// Sends a single video frame, e.g. with mime type `image/jpeg`.
+    pub async fn send_video(&mut self, frame: &[u8], mime_type: &str) -> Result<()> {
+        self.send(LiveClientMessage::RealtimeInput(LiveRealtimeInput {
+            video: Some(InlineData {
+                mime_type: mime_type.to_string(),
+                data: STANDARD.encode(frame),
+            }),
+            ..Default::default()
+        }))
+        .await
+    }
+
+    /// Signals that the audio stream was paused, flushing any cached audio on the server.
+    pub async fn end_audio_stream(&mut self) -> Result<()> {
+        self.send(LiveClientMessage::RealtimeInput(LiveRealtimeInput {
+            audio_stream_end: Some(true),
+            ..Default::default()
+        }))
+        .await
+    }
+
+    /// Answers function calls received through `LiveEvent::ToolCall`.
+    pub async fn send_tool_response(&mut self, responses: Vec<FunctionResponse>) -> Result<()> {
+        self.send(LiveClientMessage::ToolResponse(LiveToolResponse {
+            function_responses: responses,
+        }))
+        .await
+    }
+
+    /// Waits for the next event from the model.
+    ///
+    /// Returns `None` once the server closed the session normally.
+    pub async fn receive(&mut self) -> Option<Result<LiveEvent>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+
+            match self.receive_message().await {
+                Ok(Some(message)) => {
+                    if let Err(e) = self.queue_events(message) {
+                        return Some(Err(e));
+                    }
+                }
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// Closes the session, waiting for the server to acknowledge the close frame.
+    pub async fn close(mut self) -> Result<()> {
+        self.socket.close(None).await?;
+        while let Some(message) = self.socket.next().await {
+            if let WsMessage::Close(_) = message? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    async fn send(&mut self, message: LiveClientMessage) -> Result<()> {
+        let json = serde_json::to_string(&message)?;
+        self.socket.send(WsMessage::text(json)).await?;
+        Ok(())
+    }
+
+    async fn receive_message(&mut self) -> Result<Option<LiveServerMessage>> {
+        while let Some(message) = self.socket.next().await {
+            let payload = match message? {
+                WsMessage::Text(text) => text.as_bytes().to_vec(),
+                WsMessage::Binary(bytes) => bytes.to_vec(),
+                WsMessage::Close(Some(frame)) if frame.code != CloseCode::Normal => {
+                    return Err(anyhow!(
+                        "Live session closed ({}): {}",
+                        frame.code,
+                        frame.reason
+                    ));
+                }
+                WsMessage::Close(_) => return Ok(None),
+                _ => continue,
+            };
+
+            return Ok(Some(serde_json::from_slice(&payload)?));
+        }
+        Ok(None)
+    }
+
+    fn queue_events(&mut self, message: LiveServerMessage) -> Result<()> {
+        if let Some(content) = message.server_content {
+            if content.interrupted.unwrap_or(false) {
+                self.pending
+                    .retain(|event| !matches!(event, LiveEvent::Text(_) | LiveEvent::Audio(_)));
+                self.pending.push_back(LiveEvent::Interrupted);
+            }
+
+            for part in content.model_turn.map(|c| c.parts).unwrap_or_default() {
+                match part {
+                    Part::Text { text, .. } => self.pending.push_back(LiveEvent::Text(text)),
+                    Part::InlineData { inline_data, .. } => {
+                        self.pending
+                            .push_back(LiveEvent::Audio(decode_audio(&inline_data.data)?));
+                    }
+                    Part::ExecutableCode { executable_code } => self
+                        .pending
+                        .push_back(LiveEvent::ExecutableCode(executable_code)),
+                    Part::CodeExecutionResult {
+                        code_execution_result,
+                    } => self
+                        .pending
+                        .push_back(LiveEvent::CodeExecutionResult(code_execution_result)),
+                    // Function calls arrive as `toolCall` messages instead.
+                    _ => {}
+                }
+            }
+
+            if content.generation_complete.unwrap_or(false) {
+                self.pending.push_back(LiveEvent::GenerationComplete);
+            }
+            if content.turn_complete.unwrap_or(false) {
+                self.pending.push_back(LiveEvent::TurnComplete);
+            }
+        }
+
+        if let Some(tool_call) = message.tool_call {
+            self.pending
+                .push_back(LiveEvent::ToolCall(tool_call.function_calls));
+        }
+
+        if let Some(cancellation) = message.tool_call_cancellation {
+            self.pending
+                .push_back(LiveEvent::ToolCallCancellation(cancellation.ids));
+        }
+
+        if let Some(go_away) = message.go_away {
+            self.pending.push_back(LiveEvent::GoAway(go_away.time_left));
+        }
+
+        Ok(())
+    }
+}
+
+fn decode_audio(data: &str) -> Result<Vec<u8>> {
+    STANDARD
+        .decode(data)
+        .map_err(|e| anyhow!("Failed to decode audio: {}", e))
+}
@@ -8,10 +8,12 @@ use anyhow::Result;
 async fn main() -> Result<()> {
     #[cfg(feature = "cli")]
     {
+        use anyhow::anyhow;
         use clap::Parser;
         use futures_util::StreamExt;
         use gems::chat::ChatBuilder;
-        use gems::cli::{Cli, Command};
+        use gems::cli::{Cli, Command, ToolOptions};
+        use gems::cost::{load_log, UsageSummary, UsageTracker};
         use gems::embed::BatchEmbeddingBuilder;
         use gems::embed::EmbeddingBuilder;
         use gems::imagen::ImageGenBuilder;
@@ -19,6 +21,8 @@ async fn main() -> Result<()> {
         use gems::messages::Message;
         use gems::models::ModBuilder;
         use gems::models::Model;
+        use gems::rag::{AskBuilder, ChunkerBuilder, Corpus};
+        use gems::requests::Tool;
         use gems::stream::StreamBuilder;
         use gems::tokens::TokenBuilder;
         use gems::traits::CTrait;
@@ -26,79 +30,118 @@ async fn main() -> Result<()> {
         use gems::vidgen::VideoGenBuilder;
 
         use gems::tui::run_tui;
-        use gems::utils::{
-            extract_text_from_partial_json, load_and_encode_image, type_with_cursor_effect,
-        };
+        use gems::utils::{load_and_encode_image, type_with_cursor_effect};
         use gems::vision::VisionBuilder;
         use gems::Client;
         use std::env;
+        use std::path::Path;
+        use std::str::FromStr;
 
         let args: Cli = Cli::parse();
 
-        let api_key = if args.api_key.is_none() {
-            env::var("GEMINI_API_KEY").unwrap_or_default().to_owned()
-        } else {
-            args.api_key.unwrap().to_owned()
-        };
-
-        let model = if args.model.is_none() {
-            env::var("GEMINI_MODEL")
-                .unwrap_or("gemini-2.0-flash".to_string())
-                .to_owned()
-        } else {
-            args.model.unwrap().to_owned()
-        };
-        let mut gemini_client = Client::builder().model(&model).build()?;
+        fn dimmed(text: &str) -> String {
+            format!("\x1b[2m{}\x1b[0m", text)
+        }
+
+        fn tools(options: &ToolOptions) -> Option<Vec<Tool>> {
+            let tools: Vec<Tool> = [
+                (options.search, Tool::google_search as fn() -> Tool),
+                (options.url_context, Tool::url_context),
+                (options.code_execution, Tool::code_execution),
+            ]
+            .into_iter()
+            .filter_map(|(enabled, tool)| enabled.then(tool))
+            .collect();
+            (!tools.is_empty()).then_some(tools)
+        }
+
+        let api_key = args
+            .api_key
+            .unwrap_or_else(|| env::var("GEMINI_API_KEY").unwrap_or_default());
+
+        // An explicit `--model` also overrides the dedicated model of specialized commands.
+        let model_override = args.model.as_deref().map(Model::from_str).transpose()?;
+
+        let model = args
+            .model
+            .unwrap_or_else(|| env::var("GEMINI_MODEL").unwrap_or("gemini-2.0-flash".to_string()));
+        let mut builder = Client::builder().model(&model).preflight(args.preflight);
+        if let Some(path) = &args.usage_log {
+            builder = builder.usage_tracker(UsageTracker::new().with_log(path));
+        }
+        let gemini_client = builder.build()?;
 
         gemini_client.set_api_key(api_key);
         match args.cmd {
             Some(Command::Generate(cmd)) => {
-                let parameters = ChatBuilder::default()
-                    .model(Model::Flash20)
-                    .messages(vec![Message::User {
-                        content: Content::Text(cmd.text),
-                        name: None,
-                    }])
-                    .build()?;
+                let mut builder = ChatBuilder::default();
+                builder.messages(vec![Message::User {
+                    content: Content::Text(cmd.text),
+                    name: None,
+                }]);
+                if let Some(budget) = cmd.thinking.thinking_budget {
+                    builder.thinking_budget(budget);
+                }
+                if cmd.thinking.thoughts {
+                    builder.include_thoughts(true);
+                }
+                if let Some(tools) = tools(&cmd.tools) {
+                    builder.tools(tools);
+                }
+                let parameters = builder.build()?;
 
-                let response = gemini_client.chat().generate(parameters).await?;
-                println!("{}", response);
+                let response = gemini_client.chat().generate_response(parameters).await?;
+                if let Some(thoughts) = response.thoughts() {
+                    println!("{}", dimmed(&thoughts));
+                }
+                let text = if response.executable_code().is_empty() {
+                    response.cited_text()
+                } else {
+                    response.markdown()
+                }
+                .ok_or_else(|| anyhow::anyhow!("Expected text response"))?;
+                println!("{}", text);
+                if let Some(metadata) = response.grounding_metadata() {
+                    let sources = metadata.sources();
+                    if !sources.is_empty() {
+                        println!("\nSources:");
+                    }
+                    for (index, source) in sources {
+                        println!(
+                            "[{}] {} - {}",
+                            index,
+                            source.title.as_deref().unwrap_or("Untitled"),
+                            source.uri.as_deref().unwrap_or_default()
+                        );
+                    }
+                }
             }
             Some(Command::Stream(cmd)) => {
-                let parameters = StreamBuilder::default()
-                    .model(Model::Flash20)
-                    .input(Message::User {
-                        content: Content::Text(cmd.text),
-                        name: None,
-                    })
-                    .build()?;
+                let mut builder = StreamBuilder::default();
+                builder.input(Message::User {
+                    content: Content::Text(cmd.text),
+                    name: None,
+                });
+                if cmd.thinking.thoughts {
+                    builder.include_thoughts(true);
+                }
+                if let Some(budget) = cmd.thinking.thinking_budget {
+                    builder.thinking_budget(budget);
+                }
+                if let Some(tools) = tools(&cmd.tools) {
+                    builder.tools(tools);
+                }
 
-                let response = gemini_client.stream().generate(parameters).await?;
-                let mut stream = response.bytes_stream();
-                let delay = 5;
-                let mut message: String = Default::default();
-                while let Some(mut chunk) = stream.next().await {
-                    if let Ok(parsed_json) = std::str::from_utf8(chunk.as_mut().unwrap()) {
-                        if let Some(text_value) = extract_text_from_partial_json(parsed_json) {
-                            let lines: Vec<&str> = text_value
-                                .split("\\n")
-                                .flat_map(|s| s.split('\n'))
-                                .collect();
-
-                            for line in lines {
-                                message.push_str(&line.replace('\\', ""));
-                                if !line.is_empty() {
-                                    type_with_cursor_effect(&line.replace('\\', ""), delay);
-                                } else {
-                                    println!("\n");
-                                }
-                            }
-                        }
-                    } else {
-                        eprintln!("Failed to parse chunk: {:?}", chunk.as_ref().unwrap());
+                let mut chunks = Box::pin(gemini_client.stream().chunks(builder.build()?).await?);
+                while let Some(chunk) = chunks.next().await {
+                    let chunk = chunk?;
+                    if let Some(thoughts) = chunk.thoughts() {
+                        print!("{}", dimmed(&thoughts));
+                    }
+                    if let Some(text) = chunk.text() {
+                        type_with_cursor_effect(&text, 5);
                     }
                 }
-
                 println!();
             }
             Some(Command::Count(cmd)) => {
@@ -110,17 +153,36 @@ async fn main() -> Result<()> {
                     .build()?;
 
                 let count = gemini_client.tokens().count(params).await?;
-                println!("Token Count: {:?}", count);
+                println!("Token Count: {}", count.total_tokens);
+                for detail in &count.prompt_tokens_details {
+                    println!(
+                        "  {}: {}",
+                        detail.modality.as_deref().unwrap_or("UNKNOWN"),
+                        detail.token_count.unwrap_or(0)
+                    );
+                }
+                if let Some(cached) = count.cached_content_token_count {
+                    println!("  Cached: {}", cached);
+                }
             }
             Some(Command::Embed(cmd)) => {
-                let params = EmbeddingBuilder::default()
-                    .model(Model::Embedding)
+                let mut builder = EmbeddingBuilder::default();
+                builder
+                    .model(model_override.unwrap_or(Model::Embedding))
                     .input(Message::User {
                         content: Content::Text(cmd.text),
                         name: None,
-                    })
-                    .build()?;
-                gemini_client.set_model(Model::Embedding);
+                    });
+                if let Some(task_type) = cmd.options.task_type {
+                    builder.task_type(task_type);
+                }
+                if let Some(title) = cmd.options.title {
+                    builder.title(title);
+                }
+                if let Some(dimensions) = cmd.options.dimensions {
+                    builder.output_dimensionality(dimensions);
+                }
+                let params = builder.build()?;
                 let response = gemini_client.embeddings().create(params).await?;
                 println!("Embed Content: {:?}", response);
             }
@@ -133,12 +195,21 @@ async fn main() -> Result<()> {
                         name: None,
                     })
                     .collect();
-                let params = BatchEmbeddingBuilder::default()
-                    .model(Model::Embedding)
-                    .input(texts)
-                    .build()?;
+                let mut builder = BatchEmbeddingBuilder::default();
+                builder
+                    .model(model_override.unwrap_or(Model::Embedding))
+                    .input(texts);
+                if let Some(task_type) = cmd.options.task_type {
+                    builder.task_type(task_type);
+                }
+                if let Some(title) = cmd.options.title {
+                    builder.title(title);
+                }
+                if let Some(dimensions) = cmd.options.dimensions {
+                    builder.output_dimensionality(dimensions);
+                }
+                let params = builder.build()?;
 
-                gemini_client.set_model(Model::Embedding);
                 let response = gemini_client.embeddings().batch(params).await?;
                 println!("Batch Embed Contents: {:?}", response);
             }
@@ -164,23 +235,30 @@ async fn main() -> Result<()> {
                 println!("{}", result);
             }
             Some(Command::Info(_)) => {
-                let params = ModBuilder::default().model(Model::default()).build()?;
+                let params = ModBuilder::default()
+                    .model(gemini_client.get_model())
+                    .build()?;
                 let model_info = gemini_client.models().get(params).await?;
                 model_info.print();
             }
-            Some(Command::List(_)) => {
-                let models = gemini_client.models().list().await?;
-                models.print();
-            }
+            Some(Command::List(cmd)) => match cmd.method {
+                Some(method) => {
+                    for model in gemini_client.models().supporting(&method).await? {
+                        model.print();
+                    }
+                }
+                None => {
+                    let models = gemini_client.models().list().await?;
+                    models.print();
+                }
+            },
             Some(Command::Imagen(cmd)) => {
-                gemini_client.set_model(Model::FlashExpImage);
-
                 let params = ImageGenBuilder::default()
                     .input(Message::User {
                         content: Content::Text(cmd.text),
                         name: None,
                     })
-                    .model(Model::FlashExpImage)
+                    .model(model_override.unwrap_or(Model::FlashExpImage))
                     .build()
                     .unwrap();
 
@@ -189,10 +267,8 @@ async fn main() -> Result<()> {
                 tokio::fs::write("output.png", &image_data).await?;
             }
             Some(Command::Vidgen(cmd)) => {
-                gemini_client.set_model(Model::Veo2);
-
                 let params = VideoGenBuilder::default()
-                    .model(Model::Veo2)
+                    .model(model_override.unwrap_or(Model::Veo2))
                     .input(Message::User {
                         content: Content::Text(cmd.text),
                         name: None,
@@ -205,10 +281,8 @@ async fn main() -> Result<()> {
                 tokio::fs::write("output.mp4", &bytes).await?;
             }
             Some(Command::Tts(cmd)) => {
-                gemini_client.set_model(Model::Tts);
-
                 let params = TtsGenBuilder::default()
-                    .model(Model::Tts)
+                    .model(model_override.unwrap_or(Model::Tts))
                     .input(Message::User {
                         content: Content::Text(cmd.text),
                         name: None,
@@ -217,9 +291,82 @@ async fn main() -> Result<()> {
                     .build()
                     .unwrap();
 
-                let bytes = gemini_client.tts().generate(params).await?;
+                if cmd.stream {
+                    use tokio::io::AsyncWriteExt;
+
+                    let mut file = tokio::fs::File::create("output.pcm").await?;
+                    let mut chunks = Box::pin(gemini_client.tts().stream(params).await?);
+                    while let Some(chunk) = chunks.next().await {
+                        file.write_all(&chunk?).await?;
+                    }
+                    file.flush().await?;
+                } else {
+                    let bytes = gemini_client.tts().generate(params).await?;
+
+                    tokio::fs::write("output.pcm", &bytes).await?;
+                }
+            }
+            Some(Command::Ask(cmd)) => {
+                use gems::embed::index::VectorIndex;
+
+                let index = match cmd.index.as_deref() {
+                    Some(path) if std::path::Path::new(path).exists() => VectorIndex::load(path)?,
+                    _ => {
+                        let chunker = ChunkerBuilder::default()
+                            .size(cmd.chunk_size)
+                            .overlap(cmd.overlap)
+                            .build()?;
+                        let chunks = Corpus::from_dir(&cmd.corpus)?.chunks(&chunker);
+                        let index = gemini_client.rag().index(&chunks, None, None).await?;
+                        if let Some(path) = cmd.index.as_deref() {
+                            index.save(path)?;
+                        }
+                        index
+                    }
+                };
 
-                tokio::fs::write("output.pcm", &bytes).await?;
+                let params = AskBuilder::default()
+                    .question(cmd.question)
+                    .top_k(cmd.top_k)
+                    .build()?;
+                let answer = gemini_client.rag().ask(&index, params).await?;
+
+                println!("{}", answer.text);
+                if !answer.sources.is_empty() {
+                    println!("\nSources:");
+                    for source in &answer.sources {
+                        let marker = if answer.citations.contains(&source.id) {
+                            "*"
+                        } else {
+                            " "
+                        };
+                        println!("{} [{}] {:.3}", marker, source.id, source.score);
+                    }
+                }
+            }
+            Some(Command::Usage(cmd)) => {
+                let Some(path) = cmd.log.or(args.usage_log) else {
+                    return Err(anyhow!(
+                        "No usage log configured, pass `--log <file>` or `--usage-log <file>`"
+                    ));
+                };
+                if !Path::new(&path).exists() {
+                    println!(
+                        "No usage logged in {} yet, run commands with `--usage-log {}` first.",
+                        path, path
+                    );
+                } else {
+                    let records = load_log(&path)?;
+                    UsageSummary::from_records(&records).print();
+                }
+            }
+            #[cfg(feature = "server")]
+            Some(Command::Serve(cmd)) => {
+                println!(
+                    "Serving the OpenAI-compatible API on http://{}:{}/v1",
+                    cmd.host, cmd.port
+                );
+                gems::server::serve(gemini_client, (cmd.host.as_str(), cmd.port)).await?;
             }
             None => {
                 let _ = run_tui().await;
@@ -1,8 +1,26 @@
-use crate::requests::Part;
+use crate::requests::{FunctionResponse, Part};
+use crate::responses::FunctionCall;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq)]
+/// Serialized as a string, as `{"mime_type", "data"}` for images, or as
+/// `{"function_call"}` and `{"function_response"}` in function calling turns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
 pub enum Content {
     Text(String),
+    /// Base64-encoded image data.
+    Image {
+        mime_type: String,
+        data: String,
+    },
+    /// A function call requested by the model, in a `Message::Model`.
+    FunctionCall {
+        function_call: FunctionCall,
+    },
+    /// The result of a function call, in a `Message::User`.
+    FunctionResponse {
+        function_response: FunctionResponse,
+    },
 }
 
 impl Default for Content {
@@ -11,23 +29,32 @@ impl Default for Content {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// Serialized with its role as a tag, e.g. `{"role": "user", "content": "Hi"}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "role", rename_all = "lowercase")]
 pub enum Message {
     User {
         content: Content,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         name: Option<String>,
     },
     System {
         content: Content,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         name: Option<String>,
     },
     Developer {
         content: Content,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         name: Option<String>,
     },
     Tool {
         content: String,
     },
+    /// A previous answer of the model in a multi-turn conversation.
+    Model {
+        content: Content,
+    },
 }
 
 impl Default for Message {
@@ -44,10 +71,27 @@ impl Message {
         match self {
             Message::User { content, .. }
             | Message::System { content, .. }
-            | Message::Developer { content, .. } => match content {
+            | Message::Developer { content, .. }
+            | Message::Model { content } => match content {
                 Content::Text(text) => Part::text(text),
+                Content::Image { mime_type, data } => Part::inline_data(mime_type, data),
+                Content::FunctionCall { function_call } => Part::FunctionCall {
+                    function_call: function_call.clone(),
+                    thought_signature: None,
+                },
+                Content::FunctionResponse { function_response } => Part::FunctionResponse {
+                    function_response: function_response.clone(),
+                },
             },
             Message::Tool { content } => Part::text(content),
         }
     }
+
+    /// The role of the conversation turn the message belongs to.
+    pub fn role(&self) -> &'static str {
+        match self {
+            Message::Model { .. } => "model",
+            _ => "user",
+        }
+    }
 }
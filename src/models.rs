@@ -1,13 +1,16 @@
 use crate::responses::ModelInfo;
 use crate::responses::ModelsResponse;
 use crate::traits::CTrait;
+use crate::utils::error_for_status;
 use crate::Client;
 use anyhow::{anyhow, Error};
 use derive_builder::Builder;
+use futures_util::stream::{self, Stream, TryStreamExt};
 use reqwest::Method;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::str::FromStr;
 
-#[derive(Debug, Clone, Default, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub enum Model {
     Pro25Preview,
     #[default]
@@ -22,6 +25,8 @@ pub enum Model {
     Tts,
     Flash20Live,
     FlashExpImage,
+    /// Any other model id, e.g. a newer or version-pinned model, or a `tunedModels/...` name.
+    Custom(String),
 }
 
 #[allow(clippy::to_string_trait_impl)]
@@ -40,15 +45,38 @@ impl ToString for Model {
             Model::Tts => "gemini-2.5-flash-preview-tts",
             Model::Flash20Live => "gemini-2.0-flash-live-001",
             Model::FlashExpImage => "gemini-2.0-flash-exp-image-generation",
+            Model::Custom(name) => name,
         }
         .to_string()
     }
 }
 
+impl Model {
+    /// Returns the API resource name, e.g. `models/gemini-2.0-flash` or `tunedModels/my-model`.
+    pub fn resource_name(&self) -> String {
+        let name = self.to_string();
+        if name.starts_with("tunedModels/") {
+            name
+        } else {
+            format!("models/{}", name)
+        }
+    }
+}
+
+fn is_valid_model_id(id: &str) -> bool {
+    id.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_'))
+}
+
 impl FromStr for Model {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix("models/").unwrap_or(s);
+
         match s {
             "gemini-2.5-pro-preview-03-25" => Ok(Model::Pro25Preview),
             "gemini-2.0-flash" => Ok(Model::Flash20),
@@ -61,11 +89,32 @@ impl FromStr for Model {
             "veo-2.0-generate-001" => Ok(Model::Veo2),
             "gemini-2.5-flash-preview-tts" => Ok(Model::Tts),
             "gemini-2.0-flash-live-001" => Ok(Model::Flash20Live),
-            "gemini-2.0-flash-exp-image-generation" => Ok(Model::Flash20Live),
-            _ => Err(anyhow!("Unknown model: {}", s)),
+            "gemini-2.0-flash-exp-image-generation" => Ok(Model::FlashExpImage),
+            _ => {
+                let id = s.strip_prefix("tunedModels/").unwrap_or(s);
+                if is_valid_model_id(id) {
+                    Ok(Model::Custom(s.to_string()))
+                } else {
+                    Err(anyhow!("Invalid model name: {:?}", s))
+                }
+            }
         }
     }
 }
+
+impl Serialize for Model {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Model {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Model::from_str(&name).map_err(de::Error::custom)
+    }
+}
+
 #[derive(Clone)]
 pub struct Models {
     pub client: Client,
@@ -78,17 +127,88 @@ pub struct Mod {
 }
 
 impl Models {
+    /// Lists every available model, following `nextPageToken` until the last page.
     pub async fn list(&self) -> anyhow::Result<ModelsResponse> {
-        let req = self.client.request(Method::GET, "models")?;
+        let models = self.stream().try_collect().await?;
+
+        Ok(ModelsResponse {
+            models,
+            next_page_token: None,
+        })
+    }
+
+    /// Fetches a single page of models, starting at `page_token` when given.
+    pub async fn list_page(
+        &self,
+        page_size: Option<u32>,
+        page_token: Option<&str>,
+    ) -> anyhow::Result<ModelsResponse> {
+        let mut req = self.client.request(Method::GET, "models")?;
+        if let Some(page_size) = page_size {
+            req = req.query(&[("pageSize", page_size)]);
+        }
+        if let Some(page_token) = page_token {
+            req = req.query(&[("pageToken", page_token)]);
+        }
 
-        let res = req.send().await?;
+        let res = error_for_status(self.client.send(req).await?).await?;
         Ok(res.json().await?)
     }
 
-    pub async fn get(&self, _model: Mod) -> anyhow::Result<ModelInfo> {
-        let req = self.client.request(Method::GET, "")?;
+    /// Streams every available model, fetching the next page only when needed.
+    pub fn stream(&self) -> impl Stream<Item = anyhow::Result<ModelInfo>> {
+        let models = self.clone();
+
+        stream::try_unfold(Some(None), move |page_token: Option<Option<String>>| {
+            let models = models.clone();
+            async move {
+                let Some(page_token) = page_token else {
+                    return Ok(None);
+                };
+
+                let page = models.list_page(None, page_token.as_deref()).await?;
+                let next = page
+                    .next_page_token
+                    .filter(|token| !token.is_empty())
+                    .map(Some);
+
+                Ok::<_, Error>(Some((page.models, next)))
+            }
+        })
+        .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Lists the models supporting a generation method, e.g. `embedContent`.
+    pub async fn supporting(&self, method: &str) -> anyhow::Result<Vec<ModelInfo>> {
+        self.stream()
+            .try_filter(|model| futures_util::future::ready(model.supports(method)))
+            .try_collect()
+            .await
+    }
+
+    /// Returns the metadata of `model`, fetching it only on the first call for that model.
+    pub async fn info(&self, model: &Model) -> anyhow::Result<ModelInfo> {
+        if let Some(info) = self.client.cached_model_info(model) {
+            return Ok(info);
+        }
+
+        let info = self
+            .get(Mod {
+                model: model.clone(),
+            })
+            .await?;
+        self.client.cache_model_info(model, info.clone());
+        Ok(info)
+    }
+
+    /// Fetches the metadata of the requested model.
+    pub async fn get(&self, params: Mod) -> anyhow::Result<ModelInfo> {
+        let req = self
+            .client
+            .resource_request(Method::GET, &params.model.resource_name())?;
 
-        let res = req.send().await?;
+        let res = error_for_status(self.client.send(req).await?).await?;
         Ok(res.json().await?)
     }
 }
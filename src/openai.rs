@@ -0,0 +1,583 @@
+//! The OpenAI Chat Completions and Embeddings wire format.
+//!
+//! Used by `Transport::OpenAi` to call Gemini's compatibility layer or any
+//! OpenAI-compatible server, and by the `server` feature to accept such requests.
+
+use crate::client::Client;
+use crate::models::Model;
+use crate::requests::{Content, FunctionDeclaration, GeminiEmbedRequest, GeminiRequest, Part};
+use crate::responses::{Candidate, Embedding, FunctionCall, GeminiResponse, UsageMetadata};
+use crate::utils::{error_for_status, sse_events};
+use anyhow::{anyhow, Result};
+use futures_util::{future, stream, Stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// The base URL of Gemini's OpenAI compatibility layer.
+pub const GEMINI_OPENAI_URL: &str = "https://generativelanguage.googleapis.com/v1beta/openai";
+
+/// A request to `/chat/completions`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChatCompletionRequest {
+    #[serde(default)]
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stream: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<ChatTool>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// `system`, `developer`, `user`, `assistant` or `tool`, omitted in streamed deltas.
+    #[serde(default)]
+    pub role: String,
+    #[serde(default)]
+    pub content: Option<MessageContent>,
+    /// Function calls requested by an `assistant` message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// The call a `tool` message answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text {
+        text: String,
+    },
+    /// Only `data:` URLs are supported.
+    ImageUrl {
+        image_url: ImageUrl,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageUrl {
+    pub url: String,
+}
+
+/// A function the model may call, `{"type": "function", "function": {...}}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatTool {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub function: FunctionDeclaration,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    #[serde(default)]
+    pub id: String,
+    #[serde(rename = "type", default = "function_kind")]
+    pub kind: String,
+    pub function: ToolCallFunction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallFunction {
+    pub name: String,
+    /// The arguments as a JSON-encoded object.
+    #[serde(default)]
+    pub arguments: String,
+}
+
+fn function_kind() -> String {
+    "function".to_string()
+}
+
+/// A response of `/chat/completions`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatCompletion {
+    #[serde(default)]
+    pub choices: Vec<Choice>,
+    pub usage: Option<CompletionUsage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Choice {
+    #[serde(default)]
+    pub message: ChatMessage,
+    pub finish_reason: Option<String>,
+}
+
+/// A streamed chunk of `/chat/completions`, the usage coming in a last chunk without
+/// choices.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatCompletionChunk {
+    #[serde(default)]
+    pub choices: Vec<ChunkChoice>,
+    pub usage: Option<CompletionUsage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChunkChoice {
+    #[serde(default)]
+    pub delta: ChatDelta,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ChatDelta {
+    pub content: Option<String>,
+    pub tool_calls: Option<Vec<ToolCallDelta>>,
+}
+
+/// A fragment of a streamed tool call. Only the first fragment of a call has its id and
+/// name; the arguments are split over all of them.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolCallDelta {
+    #[serde(default)]
+    pub index: usize,
+    pub id: Option<String>,
+    pub function: Option<ToolCallFunctionDelta>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolCallFunctionDelta {
+    pub name: Option<String>,
+    pub arguments: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompletionUsage {
+    #[serde(default)]
+    pub prompt_tokens: i32,
+    #[serde(default)]
+    pub completion_tokens: i32,
+    #[serde(default)]
+    pub total_tokens: i32,
+}
+
+/// A request to `/embeddings`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingRequest {
+    #[serde(default)]
+    pub model: String,
+    pub input: EmbeddingInput,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingInput {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// A response of `/embeddings`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbeddingResponse {
+    pub data: Vec<EmbeddingData>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbeddingData {
+    #[serde(default)]
+    pub index: usize,
+    pub embedding: Vec<f64>,
+}
+
+fn unsupported(feature: &str) -> anyhow::Error {
+    anyhow!("{} is not supported by the OpenAI transport", feature)
+}
+
+fn join_text(parts: &[Part]) -> Result<String> {
+    parts
+        .iter()
+        .map(|part| match part {
+            Part::Text { text, .. } => Ok(text.as_str()),
+            _ => Err(unsupported("Non-text input")),
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(|texts| texts.join("\n"))
+}
+
+/// Translates the parts of a turn to messages, splitting out function responses as
+/// `tool` messages.
+fn turn_messages(content: &Content, messages: &mut Vec<ChatMessage>) -> Result<()> {
+    let role = match content.role.as_deref() {
+        Some("model") => "assistant",
+        _ => "user",
+    };
+    let mut parts = Vec::new();
+    let mut tool_calls = Vec::new();
+
+    for part in &content.parts {
+        match part {
+            Part::Text { text, .. } => parts.push(ContentPart::Text { text: text.clone() }),
+            Part::Thought { .. } => {}
+            Part::InlineData { inline_data, .. } if inline_data.mime_type.starts_with("image/") => {
+                parts.push(ContentPart::ImageUrl {
+                    image_url: ImageUrl {
+                        url: format!("data:{};base64,{}", inline_data.mime_type, inline_data.data),
+                    },
+                })
+            }
+            Part::FunctionCall { function_call, .. } => tool_calls.push(ToolCall {
+                id: function_call
+                    .id
+                    .clone()
+                    .unwrap_or_else(|| function_call.name.clone()),
+                kind: function_kind(),
+                function: ToolCallFunction {
+                    name: function_call.name.clone(),
+                    arguments: function_call
+                        .args
+                        .as_ref()
+                        .map(Value::to_string)
+                        .unwrap_or_else(|| "{}".to_string()),
+                },
+            }),
+            Part::FunctionResponse { function_response } => messages.push(ChatMessage {
+                role: "tool".to_string(),
+                content: Some(MessageContent::Text(function_response.response.to_string())),
+                tool_call_id: Some(
+                    function_response
+                        .id
+                        .clone()
+                        .unwrap_or_else(|| function_response.name.clone()),
+                ),
+                ..Default::default()
+            }),
+            Part::InlineData { .. } => return Err(unsupported("Non-image inline data")),
+            Part::FileData { .. } => return Err(unsupported("File data")),
+            _ => return Err(unsupported("Code execution and unknown parts")),
+        }
+    }
+
+    if parts.is_empty() && tool_calls.is_empty() {
+        return Ok(());
+    }
+    let content = match parts.as_slice() {
+        [] => None,
+        parts if parts.iter().all(|p| matches!(p, ContentPart::Text { .. })) => {
+            Some(MessageContent::Text(
+                parts
+                    .iter()
+                    .filter_map(|part| match part {
+                        ContentPart::Text { text, .. } => Some(text.as_str()),
+                        ContentPart::ImageUrl { .. } => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ))
+        }
+        _ => Some(MessageContent::Parts(parts)),
+    };
+    messages.push(ChatMessage {
+        role: role.to_string(),
+        content,
+        tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+        tool_call_id: None,
+    });
+    Ok(())
+}
+
+impl ChatCompletionRequest {
+    /// Translates a `generateContent` request, failing on Gemini-only features such as
+    /// cached content or the built-in tools. Thinking options are not sent.
+    pub fn from_gemini(model: &Model, request: &GeminiRequest) -> Result<Self> {
+        if request.cached_content.is_some() {
+            return Err(unsupported("Cached content"));
+        }
+
+        let mut messages = Vec::new();
+        if let Some(system) = &request.system_instruction {
+            messages.push(ChatMessage {
+                role: "system".to_string(),
+                content: Some(MessageContent::Text(join_text(&system.parts)?)),
+                ..Default::default()
+            });
+        }
+        for content in &request.contents {
+            turn_messages(content, &mut messages)?;
+        }
+
+        let mut tools = Vec::new();
+        for tool in request.tools.iter().flatten() {
+            if tool.google_search.is_some()
+                || tool.url_context.is_some()
+                || tool.code_execution.is_some()
+            {
+                return Err(unsupported("Built-in tools"));
+            }
+            tools.extend(
+                tool.function_declarations
+                    .iter()
+                    .flatten()
+                    .map(|function| ChatTool {
+                        kind: function_kind(),
+                        function: function.clone(),
+                    }),
+            );
+        }
+
+        Ok(ChatCompletionRequest {
+            model: model.to_string(),
+            messages,
+            tools: (!tools.is_empty()).then_some(tools),
+            ..Default::default()
+        })
+    }
+}
+
+/// Maps an OpenAI finish reason to its Gemini equivalent.
+fn finish_reason(reason: &str) -> String {
+    match reason {
+        "length" => "MAX_TOKENS",
+        "content_filter" => "SAFETY",
+        _ => "STOP",
+    }
+    .to_string()
+}
+
+impl From<ChatCompletion> for GeminiResponse {
+    fn from(completion: ChatCompletion) -> Self {
+        let candidates = completion
+            .choices
+            .into_iter()
+            .map(|choice| {
+                let message = choice.message;
+                let mut parts = Vec::new();
+                if let Some(MessageContent::Text(text)) = message.content {
+                    parts.push(Part::text(&text));
+                }
+                parts.extend(message.tool_calls.into_iter().flatten().map(|call| {
+                    Part::FunctionCall {
+                        function_call: FunctionCall {
+                            id: (!call.id.is_empty()).then_some(call.id),
+                            name: call.function.name,
+                            args: serde_json::from_str(&call.function.arguments).ok(),
+                        },
+                        thought_signature: None,
+                    }
+                }));
+
+                Candidate {
+                    content: Content {
+                        role: Some("model".to_string()),
+                        parts,
+                    },
+                    finish_reason: choice.finish_reason.as_deref().map(finish_reason),
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        GeminiResponse {
+            candidates: (!candidates.is_empty()).then_some(candidates),
+            usage_metadata: completion.usage.map(UsageMetadata::from),
+            extra: Default::default(),
+        }
+    }
+}
+
+/// A tool call assembled from the fragments received so far.
+#[derive(Debug, Default)]
+struct PendingToolCall {
+    id: Option<String>,
+    name: String,
+    arguments: String,
+}
+
+impl PendingToolCall {
+    fn into_part(self) -> Result<Part> {
+        let args =
+            if self.arguments.trim().is_empty() {
+                None
+            } else {
+                Some(serde_json::from_str(&self.arguments).map_err(|e| {
+                    anyhow!("Invalid arguments of the {} tool call: {}", self.name, e)
+                })?)
+            };
+        Ok(Part::FunctionCall {
+            function_call: FunctionCall {
+                id: self.id,
+                name: self.name,
+                args,
+            },
+            thought_signature: None,
+        })
+    }
+}
+
+/// Collects the tool call fragments of a stream by index, releasing the calls once their
+/// choice finishes or the stream ends.
+#[derive(Debug, Default)]
+struct ToolCalls(BTreeMap<usize, PendingToolCall>);
+
+impl ToolCalls {
+    fn push(&mut self, delta: ToolCallDelta) {
+        let call = self.0.entry(delta.index).or_default();
+        if let Some(id) = delta.id.filter(|id| !id.is_empty()) {
+            call.id = Some(id);
+        }
+        if let Some(function) = delta.function {
+            if let Some(name) = function.name {
+                call.name.push_str(&name);
+            }
+            if let Some(arguments) = function.arguments {
+                call.arguments.push_str(&arguments);
+            }
+        }
+    }
+
+    fn drain(&mut self) -> Result<Vec<Part>> {
+        std::mem::take(&mut self.0)
+            .into_values()
+            .map(PendingToolCall::into_part)
+            .collect()
+    }
+
+    /// Translates a chunk, with the complete tool calls on the chunk finishing them.
+    fn chunk(&mut self, chunk: ChatCompletionChunk) -> Result<GeminiResponse> {
+        let mut candidates = Vec::new();
+        for choice in chunk.choices {
+            let mut parts = Vec::new();
+            if let Some(text) = choice.delta.content.filter(|text| !text.is_empty()) {
+                parts.push(Part::text(&text));
+            }
+            for delta in choice.delta.tool_calls.into_iter().flatten() {
+                self.push(delta);
+            }
+            if choice.finish_reason.is_some() {
+                parts.extend(self.drain()?);
+            }
+            candidates.push(Candidate {
+                content: Content {
+                    role: Some("model".to_string()),
+                    parts,
+                },
+                finish_reason: choice.finish_reason.as_deref().map(finish_reason),
+                ..Default::default()
+            });
+        }
+
+        Ok(GeminiResponse {
+            candidates: (!candidates.is_empty()).then_some(candidates),
+            usage_metadata: chunk.usage.map(UsageMetadata::from),
+            extra: Default::default(),
+        })
+    }
+
+    /// The calls of a stream that ended without a finish reason.
+    fn finish(&mut self) -> Result<Option<GeminiResponse>> {
+        if self.0.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(GeminiResponse {
+            candidates: Some(vec![Candidate {
+                content: Content {
+                    role: Some("model".to_string()),
+                    parts: self.drain()?,
+                },
+                finish_reason: Some(finish_reason("tool_calls")),
+                ..Default::default()
+            }]),
+            usage_metadata: None,
+            extra: Default::default(),
+        }))
+    }
+}
+
+impl From<CompletionUsage> for UsageMetadata {
+    fn from(usage: CompletionUsage) -> Self {
+        UsageMetadata {
+            prompt_token_count: Some(usage.prompt_tokens),
+            candidates_token_count: Some(usage.completion_tokens),
+            total_token_count: Some(usage.total_tokens),
+            ..Default::default()
+        }
+    }
+}
+
+/// Sends `request` to `/chat/completions`.
+pub(crate) async fn generate(
+    client: &Client,
+    model: &Model,
+    request: &GeminiRequest,
+) -> Result<GeminiResponse> {
+    let body = ChatCompletionRequest::from_gemini(model, request)?;
+    let req = client.openai_request("chat/completions").json(&body);
+
+    let res = error_for_status(client.send(req).await?).await?;
+    let completion: ChatCompletion = res.json().await?;
+    Ok(completion.into())
+}
+
+/// Streams `request` from `/chat/completions`, with the usage in a final chunk without
+/// candidates.
+pub(crate) async fn stream(
+    client: &Client,
+    model: &Model,
+    request: &GeminiRequest,
+) -> Result<impl Stream<Item = Result<GeminiResponse>>> {
+    let body = ChatCompletionRequest {
+        stream: true,
+        stream_options: Some(json!({ "include_usage": true })),
+        ..ChatCompletionRequest::from_gemini(model, request)?
+    };
+    let req = client.openai_request("chat/completions").json(&body);
+
+    let res = error_for_status(client.send(req).await?).await?;
+    let events = sse_events(res)
+        .try_take_while(|data| future::ready(Ok(data != "[DONE]")))
+        .boxed();
+    Ok(stream::try_unfold(
+        (events, ToolCalls::default()),
+        |(mut events, mut calls)| async move {
+            let response = match events.try_next().await? {
+                Some(data) => Some(calls.chunk(serde_json::from_str(&data)?)?),
+                None => calls.finish()?,
+            };
+            Ok(response.map(|response| (response, (events, calls))))
+        },
+    ))
+}
+
+/// Embeds the text of `requests` with `/embeddings`, in order.
+pub(crate) async fn embed(
+    client: &Client,
+    model: &Model,
+    requests: &[GeminiEmbedRequest],
+) -> Result<Vec<Embedding>> {
+    let body = EmbeddingRequest {
+        model: model.to_string(),
+        input: EmbeddingInput::Many(
+            requests
+                .iter()
+                .map(|request| join_text(&request.content.parts))
+                .collect::<Result<_>>()?,
+        ),
+        dimensions: requests
+            .first()
+            .and_then(|request| request.output_dimensionality),
+    };
+    let req = client.openai_request("embeddings").json(&body);
+
+    let res = error_for_status(client.send(req).await?).await?;
+    let mut json: EmbeddingResponse = res.json().await?;
+    json.data.sort_by_key(|data| data.index);
+    Ok(json
+        .data
+        .into_iter()
+        .map(|data| Embedding {
+            values: data.embedding,
+        })
+        .collect())
+}
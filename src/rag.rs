@@ -0,0 +1,317 @@
+use crate::chat::{Chat, ChatBuilder};
+use crate::client::Client;
+use crate::embed::index::{Metric, SearchResult, VectorIndex};
+use crate::embed::{BatchEmbeddingBuilder, EmbeddingBuilder};
+use crate::messages::{Content, Message};
+use crate::models::Model;
+use crate::requests::TaskType;
+use crate::traits::CTrait;
+use anyhow::{anyhow, Result};
+use derive_builder::Builder;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+
+const SYSTEM_PROMPT: &str = "Answer the question using only the provided context. \
+Cite the chunks you rely on with their ids in square brackets, e.g. [notes.md#0]. \
+If the context does not contain the answer, say that you don't know.";
+
+/// How chunk sizes are measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChunkUnit {
+    #[default]
+    Characters,
+    /// Whitespace-separated words, a cheap approximation of model tokens.
+    Tokens,
+}
+
+/// Splits documents into overlapping chunks.
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(into), default)]
+pub struct Chunker {
+    pub size: usize,
+    pub overlap: usize,
+    pub unit: ChunkUnit,
+}
+
+impl Default for Chunker {
+    fn default() -> Self {
+        Self {
+            size: 1000,
+            overlap: 200,
+            unit: ChunkUnit::Characters,
+        }
+    }
+}
+
+/// A text document of a corpus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub id: String,
+    pub text: String,
+}
+
+/// A piece of a document, identified as `{document}#{position}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub id: String,
+    pub document: String,
+    pub text: String,
+}
+
+/// A set of documents to answer questions from.
+#[derive(Debug, Clone, Default)]
+pub struct Corpus {
+    pub documents: Vec<Document>,
+}
+
+#[derive(Builder, Default, Clone)]
+#[builder(setter(into), default)]
+pub struct Ask {
+    pub question: String,
+    /// The model answering the question, the client model by default.
+    #[builder(setter(into, strip_option), default)]
+    pub model: Option<Model>,
+    /// The model embedding the question, `Model::Embedding` by default.
+    #[builder(setter(into, strip_option), default)]
+    pub embedding_model: Option<Model>,
+    /// Number of chunks given to the model as context, 4 by default.
+    #[builder(setter(into, strip_option), default)]
+    pub top_k: Option<usize>,
+    /// Must match the dimensionality used when indexing the corpus.
+    #[builder(setter(into, strip_option), default)]
+    pub output_dimensionality: Option<u32>,
+}
+
+/// An answer grounded in retrieved chunks.
+#[derive(Debug, Clone)]
+pub struct Answer {
+    pub text: String,
+    /// The chunks given to the model, best match first.
+    pub sources: Vec<SearchResult>,
+    /// Ids of the sources cited in the answer.
+    pub citations: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct Rags {
+    pub client: Client,
+}
+
+impl Chunker {
+    pub fn chunk(&self, document: &Document) -> Vec<Chunk> {
+        let size = self.size.max(1);
+        let step = size.saturating_sub(self.overlap).max(1);
+
+        let pieces: Vec<String> = match self.unit {
+            ChunkUnit::Characters => {
+                let chars: Vec<char> = document.text.chars().collect();
+                windows(chars.len(), size, step)
+                    .map(|(start, end)| chars[start..end].iter().collect())
+                    .collect()
+            }
+            ChunkUnit::Tokens => {
+                let words: Vec<&str> = document.text.split_whitespace().collect();
+                windows(words.len(), size, step)
+                    .map(|(start, end)| words[start..end].join(" "))
+                    .collect()
+            }
+        };
+
+        pieces
+            .into_iter()
+            .filter(|text| !text.trim().is_empty())
+            .enumerate()
+            .map(|(i, text)| Chunk {
+                id: format!("{}#{}", document.id, i),
+                document: document.id.clone(),
+                text,
+            })
+            .collect()
+    }
+}
+
+/// Start and end offsets of windows of `size` items, `step` items apart.
+fn windows(len: usize, size: usize, step: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..len)
+        .step_by(step)
+        .take_while(move |&start| start == 0 || start + size - step < len)
+        .map(move |start| (start, (start + size).min(len)))
+}
+
+impl Corpus {
+    pub fn new(documents: Vec<Document>) -> Self {
+        Self { documents }
+    }
+
+    /// Loads every `.txt` and `.md` file under `dir`, using the relative path as document id.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let root = dir.as_ref();
+        let mut documents = Vec::new();
+        collect_documents(root, root, &mut documents)?;
+        documents.sort_by(|a, b| a.id.cmp(&b.id));
+
+        if documents.is_empty() {
+            return Err(anyhow!("No text documents found in {}", root.display()));
+        }
+        Ok(Self { documents })
+    }
+
+    pub fn chunks(&self, chunker: &Chunker) -> Vec<Chunk> {
+        self.documents
+            .iter()
+            .flat_map(|document| chunker.chunk(document))
+            .collect()
+    }
+}
+
+fn collect_documents(root: &Path, dir: &Path, documents: &mut Vec<Document>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_documents(root, &path, documents)?;
+            continue;
+        }
+
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if matches!(extension, "txt" | "md") {
+            let id = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            documents.push(Document {
+                id,
+                text: fs::read_to_string(&path)?,
+            });
+        }
+    }
+    Ok(())
+}
+
+impl Rags {
+    /// Embeds the chunks as retrieval documents into a new index.
+    ///
+    /// Each entry keeps the chunk's document id and text as metadata.
+    pub async fn index(
+        &self,
+        chunks: &[Chunk],
+        embedding_model: Option<Model>,
+        output_dimensionality: Option<u32>,
+    ) -> Result<VectorIndex> {
+        let mut builder = BatchEmbeddingBuilder::default();
+        builder
+            .model(embedding_model.unwrap_or(Model::Embedding))
+            .input(
+                chunks
+                    .iter()
+                    .map(|chunk| Message::User {
+                        content: Content::Text(chunk.text.clone()),
+                        name: None,
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .task_type(TaskType::RetrievalDocument);
+        if let Some(dimensions) = output_dimensionality {
+            builder.output_dimensionality(dimensions);
+        }
+
+        let response = self.client.embeddings().batch(builder.build()?).await?;
+
+        let mut index = VectorIndex::new();
+        index.add_batch(
+            chunks.iter().map(|chunk| {
+                (
+                    chunk.id.clone(),
+                    json!({ "document": chunk.document, "text": chunk.text }),
+                )
+            }),
+            response,
+        )?;
+        Ok(index)
+    }
+
+    /// Returns the chunks of `index` most relevant to the question.
+    pub async fn retrieve(&self, index: &VectorIndex, params: &Ask) -> Result<Vec<SearchResult>> {
+        let mut builder = EmbeddingBuilder::default();
+        builder
+            .model(params.embedding_model.clone().unwrap_or(Model::Embedding))
+            .input(Message::User {
+                content: Content::Text(params.question.clone()),
+                name: None,
+            })
+            .task_type(TaskType::RetrievalQuery);
+        if let Some(dimensions) = params.output_dimensionality {
+            builder.output_dimensionality(dimensions);
+        }
+
+        let response = self.client.embeddings().create(builder.build()?).await?;
+        let query = response
+            .embedding
+            .ok_or_else(|| anyhow!("Missing question embedding"))?;
+
+        Ok(index.search(&query.values, params.top_k.unwrap_or(4), Metric::Cosine))
+    }
+
+    /// Retrieves context for the question and answers it with citations.
+    pub async fn ask(&self, index: &VectorIndex, params: Ask) -> Result<Answer> {
+        let sources = self.retrieve(index, &params).await?;
+        let chat = grounded_chat(&params.question, &sources, params.model)?;
+        let text = self.client.chat().generate(chat).await?;
+        let citations = cited_ids(&text, &sources);
+
+        Ok(Answer {
+            text,
+            sources,
+            citations,
+        })
+    }
+}
+
+/// Builds a chat request answering `question` from the given sources.
+pub fn grounded_chat(
+    question: &str,
+    sources: &[SearchResult],
+    model: Option<Model>,
+) -> Result<Chat> {
+    let context = sources
+        .iter()
+        .map(|source| {
+            let text = source.metadata["text"].as_str().unwrap_or_default();
+            format!("[{}]\n{}", source.id, text)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let mut builder = ChatBuilder::default();
+    builder
+        .messages(vec![Message::User {
+            content: Content::Text(format!("Context:\n{}\n\nQuestion: {}", context, question)),
+            name: None,
+        }])
+        .system(vec![Message::System {
+            content: Content::Text(SYSTEM_PROMPT.to_string()),
+            name: None,
+        }]);
+    if let Some(model) = model {
+        builder.model(model);
+    }
+    Ok(builder.build()?)
+}
+
+/// Ids of the sources referenced as `[id]` or `[id, id]` in the text, in source order.
+pub fn cited_ids(text: &str, sources: &[SearchResult]) -> Vec<String> {
+    let cited: Vec<&str> = text
+        .split('[')
+        .skip(1)
+        .filter_map(|rest| rest.split_once(']').map(|(inside, _)| inside))
+        .flat_map(|inside| inside.split([',', ';']))
+        .map(str::trim)
+        .collect();
+
+    sources
+        .iter()
+        .filter(|source| cited.contains(&source.id.as_str()))
+        .map(|source| source.id.clone())
+        .collect()
+}
@@ -0,0 +1,219 @@
+use crate::models::Model;
+use crate::requests::{Content, Part};
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{sleep_until, Instant};
+
+const MINUTE: Duration = Duration::from_secs(60);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Quotas of a model. Unset limits are not enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimits {
+    /// Requests per minute.
+    pub rpm: Option<u32>,
+    /// Tokens per minute.
+    pub tpm: Option<u32>,
+    /// Requests per day.
+    pub rpd: Option<u32>,
+}
+
+impl RateLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rpm(mut self, rpm: u32) -> Self {
+        self.rpm = Some(rpm);
+        self
+    }
+
+    pub fn tpm(mut self, tpm: u32) -> Self {
+        self.tpm = Some(tpm);
+        self
+    }
+
+    pub fn rpd(mut self, rpd: u32) -> Self {
+        self.rpd = Some(rpd);
+        self
+    }
+}
+
+/// Requests sent for one model within the last minute and day.
+#[derive(Debug, Default)]
+struct Window {
+    minute: VecDeque<(Instant, u32)>,
+    day: VecDeque<Instant>,
+}
+
+impl Window {
+    fn prune(&mut self, now: Instant) {
+        while self
+            .minute
+            .front()
+            .is_some_and(|(sent, _)| now.duration_since(*sent) >= MINUTE)
+        {
+            self.minute.pop_front();
+        }
+        while self
+            .day
+            .front()
+            .is_some_and(|sent| now.duration_since(*sent) >= DAY)
+        {
+            self.day.pop_front();
+        }
+    }
+
+    /// Returns when a request of `tokens` may be sent, `None` if it may be sent now.
+    fn next_slot(&self, limits: &RateLimits, tokens: u32) -> Option<Instant> {
+        let mut waits = Vec::new();
+
+        if let Some(rpm) = limits.rpm {
+            if self.minute.len() >= rpm as usize {
+                let (sent, _) = self.minute[self.minute.len() - rpm as usize];
+                waits.push(sent + MINUTE);
+            }
+        }
+
+        if let Some(tpm) = limits.tpm {
+            let mut used: u64 = self.minute.iter().map(|(_, t)| *t as u64).sum();
+            for (sent, sent_tokens) in &self.minute {
+                if used + tokens as u64 <= tpm as u64 {
+                    break;
+                }
+                used -= *sent_tokens as u64;
+                waits.push(*sent + MINUTE);
+            }
+        }
+
+        if let Some(rpd) = limits.rpd {
+            if self.day.len() >= rpd as usize {
+                waits.push(self.day[self.day.len() - rpd as usize] + DAY);
+            }
+        }
+
+        waits.into_iter().max()
+    }
+}
+
+/// A client-side limiter delaying requests to stay within per-model quotas.
+///
+/// Clones share their state, so one limiter attached with `CBuilder::rate_limiter` covers
+/// every clone of the `Client`, and may be shared between several clients.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    limits: HashMap<Model, RateLimits>,
+    default_limits: Option<RateLimits>,
+    count_tokens: bool,
+    windows: Arc<Mutex<HashMap<Model, Window>>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the quotas of `model`.
+    pub fn limit(mut self, model: Model, limits: RateLimits) -> Self {
+        self.limits.insert(model, limits);
+        self
+    }
+
+    /// Sets the quotas of models without their own limits.
+    pub fn default_limits(mut self, limits: RateLimits) -> Self {
+        self.default_limits = Some(limits);
+        self
+    }
+
+    /// Measures generation prompts with `countTokens` instead of estimating their size.
+    ///
+    /// More accurate for multimodal prompts, at the cost of an extra call per request.
+    pub fn count_tokens(mut self, count_tokens: bool) -> Self {
+        self.count_tokens = count_tokens;
+        self
+    }
+
+    pub(crate) fn counts_tokens(&self) -> bool {
+        self.count_tokens
+    }
+
+    pub fn limits_for(&self, model: &Model) -> Option<RateLimits> {
+        self.limits.get(model).copied().or(self.default_limits)
+    }
+
+    /// Waits until a request of `tokens` to `model` fits the quotas, then reserves it.
+    ///
+    /// Fails right away if the request alone exceeds the tokens per minute of the model,
+    /// or if a request limit of zero would never let it through.
+    pub async fn acquire(&self, model: &Model, tokens: u32) -> Result<()> {
+        let Some(limits) = self.limits_for(model) else {
+            return Ok(());
+        };
+
+        if limits.rpm == Some(0) || limits.rpd == Some(0) {
+            return Err(anyhow!(
+                "Request limits of {} must be at least one request per minute and day",
+                model.to_string()
+            ));
+        }
+
+        if limits.tpm.is_some_and(|tpm| tokens > tpm) {
+            return Err(anyhow!(
+                "Request of {} tokens exceeds the limit of {} tokens per minute for {}",
+                tokens,
+                limits.tpm.unwrap_or_default(),
+                model.to_string()
+            ));
+        }
+
+        loop {
+            let wait = {
+                let mut windows = self.windows.lock().await;
+                let window = windows.entry(model.clone()).or_default();
+                let now = Instant::now();
+                window.prune(now);
+
+                match window.next_slot(&limits, tokens) {
+                    Some(at) => at,
+                    None => {
+                        window.minute.push_back((now, tokens));
+                        window.day.push_back(now);
+                        return Ok(());
+                    }
+                }
+            };
+            sleep_until(wait).await;
+        }
+    }
+}
+
+/// Tokens billed for an image of up to 384x384 pixels.
+const IMAGE_TOKENS: u32 = 258;
+
+/// Roughly estimates the tokens of contents, at four characters of text per token.
+pub fn estimate_tokens<'a>(contents: impl IntoIterator<Item = &'a Content>) -> u32 {
+    let text_tokens = |text: &str| text.chars().count().div_ceil(4) as u32;
+
+    contents
+        .into_iter()
+        .flat_map(|content| &content.parts)
+        .map(|part| match part {
+            Part::Text { text, .. } | Part::Thought { text, .. } => text_tokens(text),
+            Part::ExecutableCode { executable_code } => text_tokens(&executable_code.code),
+            Part::CodeExecutionResult {
+                code_execution_result,
+            } => text_tokens(code_execution_result.output.as_deref().unwrap_or_default()),
+            Part::InlineData { .. } | Part::FileData { .. } => IMAGE_TOKENS,
+            Part::FunctionCall { function_call, .. } => {
+                text_tokens(&function_call.args.clone().unwrap_or_default().to_string())
+            }
+            Part::FunctionResponse { function_response } => {
+                text_tokens(&function_response.response.to_string())
+            }
+            Part::Unknown(value) => text_tokens(&value.to_string()),
+        })
+        .sum()
+}
@@ -1,4 +1,7 @@
-use serde::{Deserialize, Serialize};
+use anyhow::{anyhow, Error};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::str::FromStr;
 
 /// Request structure for content generation.
 #[derive(Debug, Serialize, Deserialize)]
@@ -15,6 +18,14 @@ pub struct GeminiRequest {
     /// Optional system-level instruction.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub system_instruction: Option<Content>,
+
+    /// Tools the model may call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+
+    /// Name of cached content used as context, e.g. `cachedContents/abc`.
+    #[serde(rename = "cachedContent", skip_serializing_if = "Option::is_none")]
+    pub cached_content: Option<String>,
 }
 
 /// Request structure for content embedding.
@@ -25,6 +36,61 @@ pub struct GeminiEmbedRequest {
 
     /// Content item for embedding.
     pub content: Content,
+
+    /// The intended downstream use of the embedding.
+    #[serde(rename = "taskType", skip_serializing_if = "Option::is_none")]
+    pub task_type: Option<TaskType>,
+
+    /// Title of the document, only used with `TaskType::RetrievalDocument`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Truncates the embedding to this many dimensions.
+    #[serde(
+        rename = "outputDimensionality",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub output_dimensionality: Option<u32>,
+}
+
+/// The downstream task an embedding is optimized for.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TaskType {
+    /// A search query to retrieve documents with.
+    RetrievalQuery,
+    /// A document of the corpus being searched.
+    RetrievalDocument,
+    /// Text compared for semantic similarity.
+    SemanticSimilarity,
+    /// Text classified according to preset labels.
+    Classification,
+    /// Text clustered by similarity.
+    Clustering,
+    /// A question answered by retrieved documents.
+    QuestionAnswering,
+    /// A statement checked against retrieved evidence.
+    FactVerification,
+    /// A natural language query to retrieve code blocks with.
+    CodeRetrievalQuery,
+}
+
+impl FromStr for TaskType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().replace('-', "_").as_str() {
+            "RETRIEVAL_QUERY" => Ok(TaskType::RetrievalQuery),
+            "RETRIEVAL_DOCUMENT" => Ok(TaskType::RetrievalDocument),
+            "SEMANTIC_SIMILARITY" => Ok(TaskType::SemanticSimilarity),
+            "CLASSIFICATION" => Ok(TaskType::Classification),
+            "CLUSTERING" => Ok(TaskType::Clustering),
+            "QUESTION_ANSWERING" => Ok(TaskType::QuestionAnswering),
+            "FACT_VERIFICATION" => Ok(TaskType::FactVerification),
+            "CODE_RETRIEVAL_QUERY" => Ok(TaskType::CodeRetrievalQuery),
+            _ => Err(anyhow!("Unknown task type: {}", s)),
+        }
+    }
 }
 /// Request structure for batch content embedding.
 #[derive(Debug, Serialize, Deserialize)]
@@ -32,58 +98,248 @@ pub struct GeminiEmbedRequests {
     /// List of requests for batch content embedding.
     pub requests: Vec<GeminiEmbedRequest>,
 }
-/// Structure representing content information.
-#[derive(Debug, Serialize, Deserialize)]
+/// Content of a conversation turn, shared by requests and responses.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 pub struct Content {
+    /// The producer of the content, `user` or `model`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
     /// List of parts composing the content.
+    #[serde(default)]
     pub parts: Vec<Part>,
 }
 
-/// Define an enum to represent different types of parts in the content.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// A part of a content, serialized in camelCase and read in either camelCase or snake_case.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum Part {
+    /// A summary of the model's reasoning, returned with `include_thoughts`.
+    Thought {
+        text: String,
+        /// Always `true`, parts with `"thought": false` are read as `Text`.
+        #[serde(deserialize_with = "deserialize_true")]
+        thought: bool,
+        /// Opaque signature of the model's reasoning, sent back unchanged in later turns.
+        #[serde(
+            rename = "thoughtSignature",
+            alias = "thought_signature",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        thought_signature: Option<String>,
+    },
     /// Represents a text part in the content.
-    Text { text: String },
-    /// Represents an image part in the content.
-    Image { inline_data: Option<ImageContent> },
+    Text {
+        text: String,
+        /// Opaque signature of the model's reasoning, sent back unchanged in later turns.
+        #[serde(
+            rename = "thoughtSignature",
+            alias = "thought_signature",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        thought_signature: Option<String>,
+    },
+    /// Inline media such as images or audio.
+    InlineData {
+        #[serde(rename = "inlineData", alias = "inline_data")]
+        inline_data: InlineData,
+        #[serde(
+            rename = "videoMetadata",
+            alias = "video_metadata",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        video_metadata: Option<VideoMetadata>,
+    },
+    /// Media referenced by URI, e.g. a file uploaded with the Files API.
+    FileData {
+        #[serde(rename = "fileData", alias = "file_data")]
+        file_data: FileData,
+        #[serde(
+            rename = "videoMetadata",
+            alias = "video_metadata",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        video_metadata: Option<VideoMetadata>,
+    },
+    /// A function call requested by the model.
+    FunctionCall {
+        #[serde(rename = "functionCall", alias = "function_call")]
+        function_call: FunctionCall,
+        /// Opaque signature of the model's reasoning, sent back unchanged in later turns.
+        #[serde(
+            rename = "thoughtSignature",
+            alias = "thought_signature",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        thought_signature: Option<String>,
+    },
+    /// The result of a function call, sent back to the model.
+    FunctionResponse {
+        #[serde(rename = "functionResponse", alias = "function_response")]
+        function_response: FunctionResponse,
+    },
+    /// Code written by the model with `Tool::code_execution`.
+    ExecutableCode {
+        #[serde(rename = "executableCode", alias = "executable_code")]
+        executable_code: ExecutableCode,
+    },
+    /// The result of running an `ExecutableCode` part.
+    CodeExecutionResult {
+        #[serde(rename = "codeExecutionResult", alias = "code_execution_result")]
+        code_execution_result: CodeExecutionResult,
+    },
+    /// A part type this crate does not know yet, kept as is.
+    Unknown(Value),
 }
 
-/// Implement the `Part` enum for the `Serialize` trait
 impl Part {
     /// Create a new `Part` with text content.
     pub fn text(text: &str) -> Self {
         Part::Text {
             text: text.to_string(),
+            thought_signature: None,
         }
     }
 
-    /// Create a new `Part` with image content.
-    pub fn image(inline_data: Option<ImageContent>) -> Self {
-        Part::Image { inline_data }
+    /// Create a new `Part` with base64-encoded inline media.
+    pub fn inline_data(mime_type: &str, data: &str) -> Self {
+        Part::InlineData {
+            inline_data: InlineData {
+                mime_type: mime_type.to_string(),
+                data: data.to_string(),
+            },
+            video_metadata: None,
+        }
+    }
+
+    /// Create a new `Part` referencing media by URI.
+    pub fn file_data(mime_type: Option<&str>, file_uri: &str) -> Self {
+        Part::FileData {
+            file_data: FileData {
+                mime_type: mime_type.map(str::to_string),
+                file_uri: file_uri.to_string(),
+            },
+            video_metadata: None,
+        }
     }
 }
 
-/// Structure representing a candidate content.
-#[derive(Debug, Deserialize)]
-pub struct Candidate {
-    /// The content of the candidate.
-    pub content: Content,
+/// Accepts only `true`, so that the untagged `Part` falls through to the next variant.
+fn deserialize_true<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    if bool::deserialize(deserializer)? {
+        Ok(true)
+    } else {
+        Err(de::Error::custom("expected `true`"))
+    }
 }
 
-/// Structure representing the image part of the Gemini request.
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ImageContent {
-    /// The MIME type of the image.
+/// A function call requested by the model.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FunctionCall {
+    /// The id to echo back in the matching function response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// The name of the function to call.
+    pub name: String,
+
+    /// The function arguments as a JSON object.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Value>,
+}
+
+/// Base64-encoded media with its MIME type.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct InlineData {
+    /// The MIME type of the media, e.g. `image/png` or `audio/pcm`.
+    #[serde(rename = "mimeType", alias = "mime_type")]
     pub mime_type: String,
-    /// The actual image data in a base64-encoded string.
+    /// The media bytes as a base64-encoded string.
     pub data: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Media stored at a URI.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct FileData {
+    #[serde(
+        rename = "mimeType",
+        alias = "mime_type",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mime_type: Option<String>,
+    #[serde(rename = "fileUri", alias = "file_uri")]
+    pub file_uri: String,
+}
+
+/// The section of a video to process, with offsets such as `"1.5s"`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct VideoMetadata {
+    #[serde(
+        rename = "startOffset",
+        alias = "start_offset",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start_offset: Option<String>,
+    #[serde(
+        rename = "endOffset",
+        alias = "end_offset",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub end_offset: Option<String>,
+    /// Frames per second sampled from the video.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct GenerationConfig {
-    #[serde(rename = "responseModalities")]
+    #[serde(
+        rename = "responseModalities",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub response_modalities: Vec<String>,
+    #[serde(rename = "thinkingConfig", skip_serializing_if = "Option::is_none")]
+    pub thinking_config: Option<ThinkingConfig>,
+}
+
+/// Controls the reasoning of thinking models such as Gemini 2.5.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ThinkingConfig {
+    /// Maximum number of thinking tokens, `0` disables thinking where supported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking_budget: Option<i32>,
+    /// Returns summaries of the model's thoughts as `thought` parts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_thoughts: Option<bool>,
+}
+
+impl ThinkingConfig {
+    /// Returns a generation config for the given options, `None` when both are unset.
+    pub fn config(
+        thinking_budget: Option<i32>,
+        include_thoughts: Option<bool>,
+    ) -> Option<GenerationConfig> {
+        if thinking_budget.is_none() && include_thoughts.is_none() {
+            return None;
+        }
+
+        Some(GenerationConfig {
+            thinking_config: Some(ThinkingConfig {
+                thinking_budget,
+                include_thoughts,
+            }),
+            ..Default::default()
+        })
+    }
 }
 
 /// Request payload for video generation using Veo.
@@ -144,3 +400,200 @@ pub struct PrebuiltVoiceConfig {
     #[serde(rename = "voiceName")]
     pub voice_name: String,
 }
+
+/// A tool the model may call while generating content.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Tool {
+    /// Functions exposed to the model for function calling.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function_declarations: Option<Vec<FunctionDeclaration>>,
+
+    /// Grounds answers on Google Search results, see `GroundingMetadata`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub google_search: Option<GoogleSearch>,
+
+    /// Lets the model read the URLs given in the prompt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url_context: Option<UrlContext>,
+
+    /// Lets the model write and run Python code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_execution: Option<CodeExecution>,
+}
+
+impl Tool {
+    /// Create a new `Tool` exposing the given function declarations.
+    pub fn functions(function_declarations: Vec<FunctionDeclaration>) -> Self {
+        Tool {
+            function_declarations: Some(function_declarations),
+            ..Default::default()
+        }
+    }
+
+    /// Create the built-in Google Search grounding tool.
+    pub fn google_search() -> Self {
+        Tool {
+            google_search: Some(GoogleSearch {}),
+            ..Default::default()
+        }
+    }
+
+    /// Create the built-in URL context tool.
+    pub fn url_context() -> Self {
+        Tool {
+            url_context: Some(UrlContext {}),
+            ..Default::default()
+        }
+    }
+
+    /// Create the built-in code execution tool.
+    pub fn code_execution() -> Self {
+        Tool {
+            code_execution: Some(CodeExecution {}),
+            ..Default::default()
+        }
+    }
+}
+
+/// Configuration of the Google Search tool, serialized as an empty object.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct GoogleSearch {}
+
+/// Configuration of the URL context tool, serialized as an empty object.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct UrlContext {}
+
+/// Configuration of the code execution tool, serialized as an empty object.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CodeExecution {}
+
+/// Code generated by the model to be run by the code execution tool.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ExecutableCode {
+    /// The language of the code, e.g. `PYTHON`.
+    #[serde(default)]
+    pub language: String,
+    pub code: String,
+}
+
+impl ExecutableCode {
+    /// The language name used to tag fenced code blocks, e.g. `python`.
+    pub fn fence_language(&self) -> String {
+        self.language.to_ascii_lowercase()
+    }
+}
+
+/// The outcome of running `ExecutableCode`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CodeExecutionResult {
+    /// `OUTCOME_OK`, `OUTCOME_FAILED` or `OUTCOME_DEADLINE_EXCEEDED`.
+    #[serde(default)]
+    pub outcome: String,
+    /// The standard output, or the error when the code failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+impl CodeExecutionResult {
+    pub fn is_ok(&self) -> bool {
+        self.outcome == "OUTCOME_OK"
+    }
+}
+
+/// Declaration of a function the model may call.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FunctionDeclaration {
+    /// The name of the function.
+    pub name: String,
+
+    /// A description of what the function does.
+    pub description: String,
+
+    /// The parameters of the function as an OpenAPI schema object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Value>,
+}
+
+/// The result of a function call, sent back to the model.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FunctionResponse {
+    /// The id of the function call this response answers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// The name of the function that was called.
+    pub name: String,
+
+    /// The function output as a JSON object.
+    pub response: Value,
+}
+
+/// Messages sent by the client over a Live API session.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LiveClientMessage {
+    /// The first message of a session, configuring the model.
+    Setup(LiveSetup),
+    /// Conversation turns appended to the session.
+    ClientContent(LiveClientContent),
+    /// Realtime audio, video or text input.
+    RealtimeInput(LiveRealtimeInput),
+    /// Responses to function calls requested by the model.
+    ToolResponse(LiveToolResponse),
+}
+
+/// Session configuration sent when opening a Live API session.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveSetup {
+    /// The model resource name, e.g. `models/gemini-2.0-flash-live-001`.
+    pub model: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation_config: Option<LiveGenerationConfig>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_instruction: Option<Content>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveGenerationConfig {
+    pub response_modalities: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speech_config: Option<SpeechConfig>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveClientContent {
+    pub turns: Vec<Content>,
+    pub turn_complete: bool,
+}
+
+#[derive(Debug, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveRealtimeInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio: Option<InlineData>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video: Option<InlineData>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_stream_end: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveToolResponse {
+    pub function_responses: Vec<FunctionResponse>,
+}
@@ -1,29 +1,31 @@
-use crate::requests::Candidate as ReqCandidate;
+use crate::requests::{CodeExecutionResult, ExecutableCode};
+pub use crate::requests::{Content, FileData, FunctionCall, InlineData, Part, VideoMetadata};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 /// Response structure for content embedding.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct EmbedContentResponse {
     /// The embedding information.
     pub embedding: Option<Embedding>,
 }
 
 /// Response structure for batch content embedding.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BatchEmbedContentsResponse {
     /// The list of embeddings for batched contents.
     pub embeddings: Option<Vec<Embedding>>,
 }
 
 /// Structure representing embedding information.
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
 pub struct Embedding {
     /// List of values for the embedding.
     pub values: Vec<f64>,
 }
 
 /// Structure representing information about a model.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ModelInfo {
     /// The name of the model.
     pub name: String,
@@ -64,9 +66,20 @@ pub struct ModelInfo {
     /// The top-K parameter for top-k sampling in generation (optional).
     #[serde(rename = "topK")]
     pub top_k: Option<i32>,
+
+    /// Fields not modeled by this crate, see `GeminiResponse::extra`.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl ModelInfo {
+    /// Checks whether the model supports a generation method, e.g. `generateContent`.
+    pub fn supports(&self, method: &str) -> bool {
+        self.supported_generation_methods
+            .iter()
+            .any(|supported| supported == method)
+    }
+
     /// Prints the model information in a formatted way.
     pub fn print(&self) {
         println!(
@@ -105,10 +118,23 @@ Model:
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ModelsResponse {
     /// The list of models.
+    #[serde(default)]
     pub models: Vec<ModelInfo>,
+
+    /// The token to retrieve the next page, absent on the last page.
+    #[serde(rename = "nextPageToken", skip_serializing_if = "Option::is_none")]
+    pub next_page_token: Option<String>,
 }
 
 impl ModelsResponse {
+    /// Returns the models supporting a generation method, e.g. `embedContent`.
+    pub fn supporting(&self, method: &str) -> Vec<&ModelInfo> {
+        self.models
+            .iter()
+            .filter(|model| model.supports(method))
+            .collect()
+    }
+
     /// Prints information for each model in the list.
     pub fn print(&self) {
         for model in &self.models {
@@ -117,67 +143,340 @@ impl ModelsResponse {
     }
 }
 /// Response structure for content generation.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GeminiResponse {
     /// List of generated candidates.
-    pub candidates: Option<Vec<ReqCandidate>>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ImagenResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub candidates: Option<Vec<Candidate>>,
+    /// Token usage of the call.
+    #[serde(rename = "usageMetadata", skip_serializing_if = "Option::is_none")]
     pub usage_metadata: Option<UsageMetadata>,
-    pub model_version: Option<String>,
+
+    /// Fields not modeled by this crate yet, kept so new API fields never fail parsing.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl GeminiResponse {
+    /// The answer of the first candidate, without thoughts.
+    pub fn text(&self) -> Option<String> {
+        self.collect_text(false)
+    }
+
+    /// The thought summaries of the first candidate, if thoughts were requested.
+    pub fn thoughts(&self) -> Option<String> {
+        self.collect_text(true)
+    }
+
+    fn collect_text(&self, thoughts: bool) -> Option<String> {
+        let parts = &self.candidates.as_ref()?.first()?.content.parts;
+        let texts: Vec<&str> = parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::Thought { text, thought, .. } if *thought == thoughts => Some(text.as_str()),
+                Part::Text { text, .. } if !thoughts => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        (!texts.is_empty()).then(|| texts.concat())
+    }
+
+    /// The code written by the model in the first candidate, in order.
+    pub fn executable_code(&self) -> Vec<&ExecutableCode> {
+        self.parts()
+            .filter_map(|part| match part {
+                Part::ExecutableCode { executable_code } => Some(executable_code),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The results of running the code of the first candidate, in order.
+    pub fn code_execution_results(&self) -> Vec<&CodeExecutionResult> {
+        self.parts()
+            .filter_map(|part| match part {
+                Part::CodeExecutionResult {
+                    code_execution_result,
+                } => Some(code_execution_result),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The function calls requested in the first candidate, in order.
+    pub fn function_calls(&self) -> Vec<&FunctionCall> {
+        self.parts()
+            .filter_map(|part| match part {
+                Part::FunctionCall { function_call, .. } => Some(function_call),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Renders the answer of the first candidate as Markdown, with the code run by the
+    /// model in fenced code blocks followed by their outputs.
+    pub fn markdown(&self) -> Option<String> {
+        let blocks: Vec<String> = self
+            .parts()
+            .filter_map(|part| match part {
+                Part::Text { text, .. } => Some(text.trim_end().to_string()),
+                Part::ExecutableCode { executable_code } => Some(format!(
+                    "```{}\n{}\n```",
+                    executable_code.fence_language(),
+                    executable_code.code.trim_end()
+                )),
+                Part::CodeExecutionResult {
+                    code_execution_result,
+                } => {
+                    let output = code_execution_result.output.as_deref().unwrap_or_default();
+                    let label = if code_execution_result.is_ok() {
+                        "Output:".to_string()
+                    } else {
+                        format!("Output ({}):", code_execution_result.outcome)
+                    };
+                    Some(format!("{}\n```\n{}\n```", label, output.trim_end()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        (!blocks.is_empty()).then(|| blocks.join("\n\n"))
+    }
+
+    fn parts(&self) -> impl Iterator<Item = &Part> {
+        self.candidates
+            .iter()
+            .flat_map(|candidates| candidates.first())
+            .flat_map(|candidate| &candidate.content.parts)
+    }
+
+    /// The grounding metadata of the first candidate, if the answer was grounded.
+    pub fn grounding_metadata(&self) -> Option<&GroundingMetadata> {
+        self.candidates
+            .as_ref()?
+            .first()?
+            .grounding_metadata
+            .as_ref()
+    }
+
+    /// The answer of the first candidate with inline citation markers, see
+    /// `GroundingMetadata::cite_part`.
+    pub fn cited_text(&self) -> Option<String> {
+        let metadata = self.grounding_metadata();
+        let texts: Vec<String> = self
+            .parts()
+            .enumerate()
+            .filter_map(|(index, part)| match part {
+                Part::Text { text, .. } => Some(match metadata {
+                    Some(metadata) => metadata.cite_part(index, text),
+                    None => text.clone(),
+                }),
+                _ => None,
+            })
+            .collect();
+
+        (!texts.is_empty()).then(|| texts.concat())
+    }
+}
+
+/// A response generated by the model.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub struct TtsCandidate {
+pub struct Candidate {
+    /// The content of the candidate, empty when generation was blocked.
+    #[serde(default)]
     pub content: Content,
+
+    /// Why generation stopped, e.g. `STOP` or `SAFETY`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub finish_reason: Option<String>,
-    pub index: Option<i32>,
+
+    /// Sources of an answer grounded with `Tool::google_search`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grounding_metadata: Option<GroundingMetadata>,
+
+    /// URLs retrieved by `Tool::url_context`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_context_metadata: Option<UrlContextMetadata>,
+
+    /// Fields not modeled by this crate, see `GeminiResponse::extra`.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Sources and search queries behind a grounded answer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct Content {
-    pub parts: Vec<Part>,
-    pub role: Option<String>,
+pub struct GroundingMetadata {
+    /// Queries sent to Google Search.
+    #[serde(default)]
+    pub web_search_queries: Vec<String>,
+    /// Search suggestions to display along with the answer.
+    pub search_entry_point: Option<SearchEntryPoint>,
+    /// The sources the answer is grounded on.
+    #[serde(default)]
+    pub grounding_chunks: Vec<GroundingChunk>,
+    /// Links between segments of the answer and their sources.
+    #[serde(default)]
+    pub grounding_supports: Vec<GroundingSupport>,
+
+    /// Fields not modeled by this crate, see `GeminiResponse::extra`.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(untagged)]
-pub enum Part {
-    Text {
-        text: String,
-    },
-    Image {
-        #[serde(rename = "inlineData")]
-        inline_data: ImageContent,
-    },
-    Media {
-        #[serde(rename = "inlineData")]
-        inline_data: InlineData,
-    },
+impl GroundingMetadata {
+    /// Cites the text of a single-part answer, see `cite_part`.
+    pub fn cite(&self, text: &str) -> String {
+        self.cite_part(0, text)
+    }
+
+    /// Inserts `[n]` markers after every supported segment of `text`, the part at
+    /// `part_index` of the answer, where `n` is the 1-based index of the source in
+    /// `grounding_chunks`.
+    ///
+    /// Segment offsets are byte offsets into the text of their part; supports pointing
+    /// outside of `text` are skipped.
+    pub fn cite_part(&self, part_index: usize, text: &str) -> String {
+        let mut supports: Vec<(usize, String)> = self
+            .grounding_supports
+            .iter()
+            .filter(|support| support.segment.part_index == part_index)
+            .filter(|support| !support.grounding_chunk_indices.is_empty())
+            .filter_map(|support| {
+                let end = support.segment.end_index?;
+                let markers = support
+                    .grounding_chunk_indices
+                    .iter()
+                    .map(|index| format!("[{}]", index + 1))
+                    .collect::<String>();
+                (end <= text.len() && text.is_char_boundary(end)).then_some((end, markers))
+            })
+            .collect();
+        supports.sort_by_key(|(end, _)| std::cmp::Reverse(*end));
+
+        let mut cited = text.to_string();
+        for (end, markers) in supports {
+            cited.insert_str(end, &markers);
+        }
+        cited
+    }
+
+    /// The web sources, numbered as in `cite`.
+    pub fn sources(&self) -> Vec<(usize, &WebSource)> {
+        self.grounding_chunks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, chunk)| Some((index + 1, chunk.web.as_ref()?)))
+            .collect()
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Google Search suggestions returned with a grounded answer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct ImageContent {
-    pub mime_type: String,
-    pub data: String,
+pub struct SearchEntryPoint {
+    /// HTML and CSS snippet rendering the suggestions.
+    pub rendered_content: Option<String>,
+    /// Base64 encoded JSON of search terms and URLs.
+    pub sdk_blob: Option<String>,
+}
+
+/// A source an answer is grounded on.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GroundingChunk {
+    pub web: Option<WebSource>,
+}
+
+/// A web page found by Google Search.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WebSource {
+    pub uri: Option<String>,
+    pub title: Option<String>,
+}
+
+/// Sources supporting one segment of the answer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroundingSupport {
+    pub segment: Segment,
+    /// Indices into `GroundingMetadata::grounding_chunks`.
+    #[serde(default)]
+    pub grounding_chunk_indices: Vec<usize>,
+    /// Confidence of each source, in the order of `grounding_chunk_indices`.
+    #[serde(default)]
+    pub confidence_scores: Vec<f32>,
+}
+
+/// A segment of one part of the answer, in byte offsets into the text of that part.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Segment {
+    #[serde(default)]
+    pub part_index: usize,
+    pub start_index: Option<usize>,
+    pub end_index: Option<usize>,
+    pub text: Option<String>,
+}
+
+/// URLs retrieved by the URL context tool.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlContextMetadata {
+    #[serde(default)]
+    pub url_metadata: Vec<UrlMetadata>,
+}
+
+/// The retrieval status of one URL, e.g. `URL_RETRIEVAL_STATUS_SUCCESS`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlMetadata {
+    pub retrieved_url: Option<String>,
+    pub url_retrieval_status: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+pub struct ImagenResponse {
+    pub candidates: Option<Vec<Candidate>>,
+    pub usage_metadata: Option<UsageMetadata>,
+    pub model_version: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct UsageMetadata {
     pub prompt_token_count: Option<i32>,
+    pub candidates_token_count: Option<i32>,
+    pub cached_content_token_count: Option<i32>,
+    pub thoughts_token_count: Option<i32>,
     pub total_token_count: Option<i32>,
     pub prompt_tokens_details: Option<Vec<PromptTokenDetail>>,
+
+    /// Fields not modeled by this crate, see `GeminiResponse::extra`.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Response of `countTokens`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CountTokensResponse {
+    /// The number of tokens of the whole prompt.
+    pub total_tokens: i32,
+    /// The number of prompt tokens served from cached content.
+    pub cached_content_token_count: Option<i32>,
+    /// The prompt tokens per modality, e.g. `TEXT` or `IMAGE`.
+    #[serde(default)]
+    pub prompt_tokens_details: Vec<PromptTokenDetail>,
+
+    /// Fields not modeled by this crate, see `GeminiResponse::extra`.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct PromptTokenDetail {
     pub modality: Option<String>,
@@ -239,16 +538,45 @@ pub struct ErrorMessage {
 #[derive(Debug, Deserialize)]
 pub struct TtsResponse {
     pub candidates: Option<Vec<Candidate>>,
+    #[serde(rename = "usageMetadata")]
+    pub usage_metadata: Option<UsageMetadata>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct Candidate {
-    pub content: Content,
+/// Messages received from the server over a Live API session.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveServerMessage {
+    pub setup_complete: Option<Value>,
+    pub server_content: Option<LiveServerContent>,
+    pub tool_call: Option<LiveToolCall>,
+    pub tool_call_cancellation: Option<LiveToolCallCancellation>,
+    pub go_away: Option<LiveGoAway>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+/// Content generated by the model in response to client input.
+#[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct InlineData {
-    pub mime_type: String,
-    pub data: String,
+pub struct LiveServerContent {
+    pub model_turn: Option<Content>,
+    pub turn_complete: Option<bool>,
+    pub interrupted: Option<bool>,
+    pub generation_complete: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveToolCall {
+    pub function_calls: Vec<FunctionCall>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LiveToolCallCancellation {
+    pub ids: Vec<String>,
+}
+
+/// Notice that the server will soon terminate the session.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveGoAway {
+    pub time_left: Option<String>,
 }
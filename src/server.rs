@@ -0,0 +1,486 @@
+//! An OpenAI-compatible HTTP server translating Chat Completions, Embeddings and Models
+//! requests to the Gemini API.
+
+use crate::chat::{Chat, ChatBuilder};
+use crate::embed::BatchEmbeddingBuilder;
+use crate::integrations::axum::status_of;
+use crate::messages::{Content, Message};
+use crate::models::Model;
+pub use crate::openai::{
+    ChatCompletionRequest, ChatMessage, ChatTool, ContentPart, EmbeddingInput, EmbeddingRequest,
+    ImageUrl, MessageContent, ToolCall, ToolCallFunction,
+};
+use crate::requests::{FunctionResponse, Tool};
+use crate::responses::{FunctionCall, GeminiResponse, UsageMetadata};
+use crate::traits::CTrait;
+use crate::Client;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures_util::{stream, StreamExt};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::net::{TcpListener, ToSocketAddrs};
+
+/// Returns the routes of the OpenAI-compatible API, backed by `client`.
+pub fn router(client: Client) -> Router {
+    Router::new()
+        .route("/v1/chat/completions", post(chat_completions))
+        .route("/v1/embeddings", post(embeddings))
+        .route("/v1/models", get(models))
+        .with_state(client)
+}
+
+/// Serves the OpenAI-compatible API on `addr` until the process is stopped.
+pub async fn serve(client: Client, addr: impl ToSocketAddrs) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    axum::serve(listener, router(client)).await?;
+    Ok(())
+}
+
+/// An error in the OpenAI error format.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    fn invalid_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        Self {
+            status: status_of(&error),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let kind = if self.status.is_client_error() {
+            "invalid_request_error"
+        } else {
+            "api_error"
+        };
+        let body = json!({ "error": { "message": self.message, "type": kind, "code": null } });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+fn resolve_model(name: &str, default: Model) -> Result<Model, ApiError> {
+    if name.trim().is_empty() {
+        return Ok(default);
+    }
+    Model::from_str(name).map_err(|e| ApiError::invalid_request(e.to_string()))
+}
+
+impl ChatMessage {
+    /// Translates the message to one `Message` per content part and tool call, so that
+    /// they all end up in a single conversation turn.
+    ///
+    /// `calls` maps the ids of the tool calls seen so far to their function names, which
+    /// Gemini requires in function responses.
+    fn to_messages(&self, calls: &mut HashMap<String, String>) -> Result<Vec<Message>, ApiError> {
+        let mut contents = match &self.content {
+            None if self.tool_calls.is_some() => Vec::new(),
+            None => vec![Content::Text(String::new())],
+            Some(MessageContent::Text(text)) => vec![Content::Text(text.clone())],
+            Some(MessageContent::Parts(parts)) => parts
+                .iter()
+                .map(|part| match part {
+                    ContentPart::Text { text, .. } => Ok(Content::Text(text.clone())),
+                    ContentPart::ImageUrl { image_url } => image_content(&image_url.url),
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        if self.tool_calls.is_some() && self.role != "assistant" {
+            return Err(ApiError::invalid_request(
+                "Only assistant messages can contain tool calls",
+            ));
+        }
+        for call in self.tool_calls.iter().flatten() {
+            let args = serde_json::from_str(&call.function.arguments).map_err(|e| {
+                ApiError::invalid_request(format!("Invalid tool call arguments: {}", e))
+            })?;
+            calls.insert(call.id.clone(), call.function.name.clone());
+            contents.push(Content::FunctionCall {
+                function_call: FunctionCall {
+                    id: (!call.id.is_empty()).then(|| call.id.clone()),
+                    name: call.function.name.clone(),
+                    args: Some(args),
+                },
+            });
+        }
+
+        if self.role == "tool" {
+            return Ok(vec![Message::User {
+                content: self.function_response(contents, calls)?,
+                name: None,
+            }]);
+        }
+
+        contents
+            .into_iter()
+            .map(|content| match self.role.as_str() {
+                "system" => Ok(Message::System {
+                    content,
+                    name: None,
+                }),
+                "developer" => Ok(Message::Developer {
+                    content,
+                    name: None,
+                }),
+                "user" => Ok(Message::User {
+                    content,
+                    name: None,
+                }),
+                "assistant" => Ok(Message::Model { content }),
+                role => Err(ApiError::invalid_request(format!(
+                    "Unsupported message role: {}",
+                    role
+                ))),
+            })
+            .collect()
+    }
+
+    /// Translates a `tool` message to the response of the call it answers. Outputs that
+    /// are not JSON objects are wrapped as `{"content": ...}`.
+    fn function_response(
+        &self,
+        contents: Vec<Content>,
+        calls: &HashMap<String, String>,
+    ) -> Result<Content, ApiError> {
+        let id = self.tool_call_id.as_deref().unwrap_or_default();
+        let name = calls.get(id).ok_or_else(|| {
+            ApiError::invalid_request(format!("Tool message answers an unknown tool call: {}", id))
+        })?;
+        let output = contents
+            .into_iter()
+            .map(|content| match content {
+                Content::Text(text) => Ok(text),
+                _ => Err(ApiError::invalid_request("Tool messages must contain text")),
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .join("\n");
+
+        let response = match serde_json::from_str::<Value>(&output) {
+            Ok(value @ Value::Object(_)) => value,
+            Ok(value) => json!({ "content": value }),
+            Err(_) => json!({ "content": output }),
+        };
+        Ok(Content::FunctionResponse {
+            function_response: FunctionResponse {
+                id: (!id.is_empty()).then(|| id.to_string()),
+                name: name.clone(),
+                response,
+            },
+        })
+    }
+}
+
+/// Parses a `data:<mime>;base64,<data>` URL.
+fn image_content(url: &str) -> Result<Content, ApiError> {
+    url.strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"))
+        .map(|(mime_type, data)| Content::Image {
+            mime_type: mime_type.to_string(),
+            data: data.to_string(),
+        })
+        .ok_or_else(|| ApiError::invalid_request("Only base64 data URLs are supported for images"))
+}
+
+impl ChatCompletionRequest {
+    /// Translates the request to a `Chat`, with system and developer messages as instructions.
+    pub fn to_chat(&self, default_model: Model) -> Result<Chat, ApiError> {
+        let mut system = Vec::new();
+        let mut messages = Vec::new();
+        let mut calls = HashMap::new();
+        for message in &self.messages {
+            for message in message.to_messages(&mut calls)? {
+                match message {
+                    Message::System { .. } | Message::Developer { .. } => system.push(message),
+                    message => messages.push(message),
+                }
+            }
+        }
+        if messages.is_empty() {
+            return Err(ApiError::invalid_request(
+                "At least one message is required",
+            ));
+        }
+
+        let mut builder = ChatBuilder::default();
+        builder
+            .model(resolve_model(&self.model, default_model)?)
+            .messages(messages);
+        if !system.is_empty() {
+            builder.system(system);
+        }
+        if let Some(tools) = self.tools.as_ref().filter(|tools| !tools.is_empty()) {
+            let functions = tools
+                .iter()
+                .map(|tool| match tool.kind.as_str() {
+                    "function" => Ok(tool.function.clone()),
+                    kind => Err(ApiError::invalid_request(format!(
+                        "Unsupported tool type: {}",
+                        kind
+                    ))),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            builder.tools(vec![Tool::functions(functions)]);
+        }
+        builder
+            .build()
+            .map_err(|e| ApiError::invalid_request(e.to_string()))
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn completion_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    format!("chatcmpl-{:x}", nanos)
+}
+
+/// Maps a Gemini finish reason to its OpenAI equivalent.
+fn finish_reason(reason: &str) -> &'static str {
+    match reason {
+        "MAX_TOKENS" => "length",
+        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" => "content_filter",
+        _ => "stop",
+    }
+}
+
+fn response_finish_reason(response: &GeminiResponse) -> Option<&'static str> {
+    let candidate = response.candidates.as_ref()?.first()?;
+    candidate.finish_reason.as_deref().map(finish_reason)
+}
+
+/// The function calls of `response` as tool calls, numbered from `first` when the model
+/// does not assign ids.
+fn tool_calls(response: &GeminiResponse, first: usize) -> Vec<ToolCall> {
+    response
+        .function_calls()
+        .into_iter()
+        .enumerate()
+        .map(|(index, call)| ToolCall {
+            id: call
+                .id
+                .clone()
+                .unwrap_or_else(|| format!("call_{}", first + index)),
+            kind: "function".to_string(),
+            function: ToolCallFunction {
+                name: call.name.clone(),
+                arguments: call
+                    .args
+                    .as_ref()
+                    .map(Value::to_string)
+                    .unwrap_or_else(|| "{}".to_string()),
+            },
+        })
+        .collect()
+}
+
+fn usage(usage: Option<&UsageMetadata>) -> Value {
+    let count = |value: Option<i32>| value.unwrap_or(0).max(0);
+    let (prompt, completion) = usage
+        .map(|usage| {
+            (
+                count(usage.prompt_token_count),
+                count(usage.candidates_token_count) + count(usage.thoughts_token_count),
+            )
+        })
+        .unwrap_or_default();
+    json!({
+        "prompt_tokens": prompt,
+        "completion_tokens": completion,
+        "total_tokens": prompt + completion,
+    })
+}
+
+async fn chat_completions(
+    State(client): State<Client>,
+    Json(request): Json<ChatCompletionRequest>,
+) -> Result<Response, ApiError> {
+    let chat = request.to_chat(client.get_model())?;
+    let model = chat.model.clone().unwrap_or_default().to_string();
+
+    if request.stream {
+        return chat_completion_stream(client, chat, model).await;
+    }
+
+    let response = client.chat().generate_response(chat).await?;
+    let tool_calls = tool_calls(&response, 0);
+    let mut message = json!({ "role": "assistant", "content": response.text() });
+    let mut finish_reason = response_finish_reason(&response).unwrap_or("stop");
+    if !tool_calls.is_empty() {
+        message["tool_calls"] = json!(tool_calls);
+        finish_reason = "tool_calls";
+    }
+    let body = json!({
+        "id": completion_id(),
+        "object": "chat.completion",
+        "created": unix_time(),
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "message": message,
+            "finish_reason": finish_reason,
+        }],
+        "usage": usage(response.usage_metadata.as_ref()),
+    });
+    Ok(Json(body).into_response())
+}
+
+async fn chat_completion_stream(
+    client: Client,
+    chat: Chat,
+    model: String,
+) -> Result<Response, ApiError> {
+    let chunks = client.chat().stream(chat).await?;
+    let id = completion_id();
+    let created = unix_time();
+
+    let chunk = move |delta: Value, finish_reason: Option<&str>| {
+        json!({
+            "id": id,
+            "object": "chat.completion.chunk",
+            "created": created,
+            "model": model,
+            "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+        })
+    };
+
+    let first = chunk(json!({ "role": "assistant", "content": "" }), None);
+    let mut calls = 0;
+    let events = chunks.map(move |result| match result {
+        Ok(response) => {
+            let mut delta = match response.text() {
+                Some(text) => json!({ "content": text }),
+                None => json!({}),
+            };
+            let tool_calls: Vec<Value> = tool_calls(&response, calls)
+                .into_iter()
+                .enumerate()
+                .map(|(index, call)| {
+                    let mut call = json!(call);
+                    call["index"] = json!(calls + index);
+                    call
+                })
+                .collect();
+            if !tool_calls.is_empty() {
+                calls += tool_calls.len();
+                delta["tool_calls"] = json!(tool_calls);
+            }
+            let finish_reason = response_finish_reason(&response).map(|reason| {
+                if calls > 0 {
+                    "tool_calls"
+                } else {
+                    reason
+                }
+            });
+            chunk(delta, finish_reason)
+        }
+        Err(error) => json!({ "error": { "message": error.to_string(), "type": "api_error" } }),
+    });
+
+    let events = stream::once(async move { first })
+        .chain(events)
+        .map(|value| Event::default().data(value.to_string()))
+        .chain(stream::once(async { Event::default().data("[DONE]") }))
+        .map(Ok::<_, Infallible>);
+
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+async fn embeddings(
+    State(client): State<Client>,
+    Json(request): Json<EmbeddingRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let model = resolve_model(&request.model, Model::Embedding)?;
+    let inputs = match request.input {
+        EmbeddingInput::One(text) => vec![text],
+        EmbeddingInput::Many(texts) => texts,
+    };
+    if inputs.is_empty() {
+        return Err(ApiError::invalid_request("Input must not be empty"));
+    }
+
+    let mut builder = BatchEmbeddingBuilder::default();
+    builder.model(model.clone()).input(
+        inputs
+            .into_iter()
+            .map(|text| Message::User {
+                content: Content::Text(text),
+                name: None,
+            })
+            .collect::<Vec<_>>(),
+    );
+    if let Some(dimensions) = request.dimensions {
+        builder.output_dimensionality(dimensions);
+    }
+    let params = builder
+        .build()
+        .map_err(|e| ApiError::invalid_request(e.to_string()))?;
+
+    let response = client.embeddings().batch(params).await?;
+    let data: Vec<Value> = response
+        .embeddings
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .map(|(index, embedding)| {
+            json!({ "object": "embedding", "index": index, "embedding": embedding.values })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "object": "list",
+        "data": data,
+        "model": model.to_string(),
+        "usage": { "prompt_tokens": 0, "total_tokens": 0 },
+    })))
+}
+
+async fn models(State(client): State<Client>) -> Result<Json<Value>, ApiError> {
+    let response = client.models().list().await?;
+    let data: Vec<Value> = response
+        .models
+        .iter()
+        .map(|model| {
+            json!({
+                "id": model.name.trim_start_matches("models/"),
+                "object": "model",
+                "created": 0,
+                "owned_by": "google",
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({ "object": "list", "data": data })))
+}
@@ -3,9 +3,14 @@ use crate::messages::Message;
 use crate::models::Model;
 use crate::requests::Content;
 use crate::requests::GeminiRequest;
+use crate::requests::ThinkingConfig;
+use crate::requests::Tool;
+use crate::responses::GeminiResponse;
 use crate::traits::CTrait;
+use crate::utils::{error_for_status, sse_events};
 use anyhow::Result;
 use derive_builder::Builder;
+use futures_util::{Stream as FuturesStream, TryStreamExt};
 use reqwest::Method;
 use reqwest::Response;
 
@@ -17,33 +22,91 @@ pub struct Streaming {
 #[derive(Builder, Clone)]
 #[builder(setter(into))]
 pub struct Stream {
-    pub model: Model,
+    #[builder(setter(into, strip_option), default)]
+    pub model: Option<Model>,
     pub input: Message,
     #[builder(setter(into, strip_option), default)]
     pub system: Option<Vec<Message>>,
+    #[builder(setter(into, strip_option), default)]
+    pub tools: Option<Vec<Tool>>,
+    /// Maximum number of thinking tokens of thinking models.
+    #[builder(setter(into, strip_option), default)]
+    pub thinking_budget: Option<i32>,
+    /// Streams thought summaries before the answer.
+    #[builder(setter(into, strip_option), default)]
+    pub include_thoughts: Option<bool>,
 }
 
 impl Streaming {
+    /// Returns the raw `streamGenerateContent` response, a JSON array streamed in pieces.
+    ///
+    /// The body is left to the caller, so its usage is not recorded; use `chunks` to
+    /// track usage.
     pub async fn generate(&self, params: Stream) -> Result<Response> {
+        let (model, request_body) = self.prepare(params).await?;
+
+        let req = self
+            .client
+            .request_with_model(Method::POST, &model, "streamGenerateContent")?
+            .json(&request_body);
+
+        let res = self.client.send(req).await?;
+        Ok(res)
+    }
+
+    /// Streams the response as parsed chunks, separating thoughts from the answer.
+    ///
+    /// Uses server-sent events, so every chunk is a complete `GeminiResponse`.
+    pub async fn chunks(
+        &self,
+        params: Stream,
+    ) -> Result<impl FuturesStream<Item = Result<GeminiResponse>>> {
+        let (model, request_body) = self.prepare(params).await?;
+
+        let req = self
+            .client
+            .request_with_model(Method::POST, &model, "streamGenerateContent")?
+            .query(&[("alt", "sse")])
+            .json(&request_body);
+
+        let res = error_for_status(self.client.send(req).await?).await?;
+        let client = self.client.clone();
+        Ok(sse_events(res).and_then(move |data| {
+            let chunk = serde_json::from_str::<GeminiResponse>(&data)
+                .map_err(Into::into)
+                .and_then(|chunk| client.record_chunk_usage(&model, &chunk).map(|_| chunk));
+            async move { chunk }
+        }))
+    }
+
+    async fn prepare(&self, params: Stream) -> Result<(Model, GeminiRequest)> {
         let system_instruction = params.system.as_ref().map(|messages| Content {
             parts: messages.iter().map(|msg| msg.to_part()).collect(),
+            role: None,
         });
 
+        let model = params.model.unwrap_or_else(|| self.client.get_model());
+
         let request_body = GeminiRequest {
-            model: params.model.to_string(),
+            model: model.to_string(),
             contents: vec![Content {
                 parts: vec![params.input.to_part()],
+                role: None,
             }],
             system_instruction,
-            config: None,
+            config: ThinkingConfig::config(params.thinking_budget, params.include_thoughts),
+            tools: params.tools,
+            cached_content: None,
         };
 
-        let req = self
-            .client
-            .request(Method::POST, "streamGenerateContent")?
-            .json(&request_body);
+        self.client
+            .ensure_supports(&model, "generateContent")
+            .await?;
+        self.client
+            .ensure_within_limit(&model, &request_body)
+            .await?;
+        self.client.throttle(&model, &request_body).await?;
 
-        let res = req.send().await?;
-        Ok(res)
+        Ok((model, request_body))
     }
 }
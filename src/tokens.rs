@@ -1,13 +1,17 @@
+use crate::chat::Chat;
+use crate::client::Backend;
 use crate::messages::Message;
 use crate::models::Model;
 use crate::requests::Content;
 use crate::requests::GeminiRequest;
+use crate::responses::CountTokensResponse;
 use crate::traits::CTrait;
+use crate::utils::error_for_status;
 use crate::Client;
 use anyhow::Result;
 use derive_builder::Builder;
 use reqwest::Method;
-use serde_json::Value;
+use serde_json::{json, Value};
 
 #[derive(Clone)]
 pub struct Tokens {
@@ -17,34 +21,79 @@ pub struct Tokens {
 #[derive(Builder, Default, Clone)]
 #[builder(setter(into), default)]
 pub struct Token {
-    model: Model,
+    #[builder(setter(into, strip_option), default)]
+    model: Option<Model>,
     input: Message,
     system: Vec<Message>,
 }
 
 impl Tokens {
-    pub async fn count(&self, params: Token) -> Result<usize> {
-        let system_instruction = Content {
+    /// Counts the tokens of a single message, with an optional system instruction.
+    pub async fn count(&self, params: Token) -> Result<CountTokensResponse> {
+        let system_instruction = (!params.system.is_empty()).then(|| Content {
             parts: params.system.iter().map(|msg| msg.to_part()).collect(),
-        };
+            role: None,
+        });
+
+        let model = params.model.unwrap_or_else(|| self.client.get_model());
 
         let request_body = GeminiRequest {
-            model: params.model.to_string(),
+            model: model.to_string(),
             contents: vec![Content {
                 parts: vec![params.input.to_part()],
+                role: Some(params.input.role().to_string()),
             }],
-            system_instruction: Some(system_instruction),
+            system_instruction,
             config: None,
+            tools: None,
+            cached_content: None,
+        };
+
+        self.client.ensure_supports(&model, "countTokens").await?;
+        self.count_request(&model, &request_body).await
+    }
+
+    /// Counts the tokens of a whole conversation, including its system instruction, tools,
+    /// images and cached content, as it would be sent by `Chats::generate`.
+    pub async fn count_chat(&self, params: &Chat) -> Result<CountTokensResponse> {
+        let model = params
+            .model
+            .clone()
+            .unwrap_or_else(|| self.client.get_model());
+        let request_body = params.to_request(&model);
+
+        self.client.ensure_supports(&model, "countTokens").await?;
+        self.count_request(&model, &request_body).await
+    }
+
+    /// Counts the prompt tokens of a full generation request.
+    pub(crate) async fn count_request(
+        &self,
+        model: &Model,
+        request: &GeminiRequest,
+    ) -> Result<CountTokensResponse> {
+        let mut generate_content_request = serde_json::to_value(request)?;
+        let body = match self.client.backend() {
+            Backend::GeminiApi => {
+                generate_content_request["model"] = Value::String(model.resource_name());
+                json!({ "generateContentRequest": generate_content_request })
+            }
+            // Vertex AI takes the request fields directly, without cached content.
+            Backend::VertexAi { .. } => {
+                if let Some(fields) = generate_content_request.as_object_mut() {
+                    fields.remove("model");
+                    fields.remove("cachedContent");
+                }
+                generate_content_request
+            }
         };
 
         let req = self
             .client
-            .request(Method::POST, "countTokens")?
-            .json(&request_body);
-
-        let res = req.send().await?;
-        let json: Value = res.json().await?;
+            .request_with_model(Method::POST, model, "countTokens")?
+            .json(&body);
 
-        Ok(json["totalTokens"].as_u64().unwrap_or(0) as usize)
+        let res = error_for_status(self.client.send(req).await?).await?;
+        Ok(res.json().await?)
     }
 }
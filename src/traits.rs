@@ -1,8 +1,10 @@
 use crate::chat::Chats;
 use crate::embed::Embeddings;
 use crate::imagen::Images;
+use crate::live::Lives;
 use crate::models::Model;
 use crate::models::Models;
+use crate::rag::Rags;
 use crate::stream::Streaming;
 use crate::tokens::Tokens;
 use crate::tts::Tts;
@@ -18,6 +20,12 @@ pub trait CTrait {
 
     fn set_model(&mut self, model: Model);
     fn request(&self, method: Method, endpoint: &str) -> Result<RequestBuilder>;
+    fn request_with_model(
+        &self,
+        method: Method,
+        model: &Model,
+        endpoint: &str,
+    ) -> Result<RequestBuilder>;
     fn chat(&self) -> Chats;
     fn embeddings(&self) -> Embeddings;
     fn tokens(&self) -> Tokens;
@@ -27,4 +35,6 @@ pub trait CTrait {
     fn images(&self) -> Images;
     fn videos(&self) -> Videos;
     fn tts(&self) -> Tts;
+    fn live(&self) -> Lives;
+    fn rag(&self) -> Rags;
 }
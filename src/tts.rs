@@ -1,15 +1,18 @@
 use crate::client::Client;
 use crate::messages::Message;
 use crate::models::Model;
+use crate::ratelimit::estimate_tokens;
 use crate::requests::{
     Content, Part, PrebuiltVoiceConfig, SpeechConfig, TtsGenerationConfig, TtsRequest, VoiceConfig,
 };
-use crate::responses::{ErrorWrapper, Part as ResPart, TtsResponse};
+use crate::responses::{GeminiResponse, TtsResponse};
 use crate::traits::CTrait;
+use crate::utils::{error_for_status, sse_events};
 use anyhow::{anyhow, Result};
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
 use derive_builder::Builder;
+use futures_util::{Stream, TryStreamExt};
 use reqwest::Method;
 
 #[derive(Clone)]
@@ -20,7 +23,8 @@ pub struct Tts {
 #[derive(Builder, Clone)]
 #[builder(setter(into))]
 pub struct TtsGen {
-    pub model: Model,
+    #[builder(setter(into, strip_option), default)]
+    pub model: Option<Model>,
     pub input: Message,
     #[builder(setter(into, strip_option), default)]
     pub system: Option<Vec<Message>>,
@@ -30,15 +34,121 @@ pub struct TtsGen {
 
 impl Tts {
     pub async fn generate(&self, params: TtsGen) -> Result<Vec<u8>> {
+        let model = params
+            .model
+            .clone()
+            .unwrap_or_else(|| self.client.get_model());
+        let request_body = Self::build_request(&model, &params)?;
+        self.client
+            .ensure_supports(&model, "generateContent")
+            .await?;
+        let tokens = estimate_tokens(
+            request_body
+                .contents
+                .iter()
+                .chain(&request_body.system_instruction),
+        );
+        self.client.throttle_tokens(&model, tokens).await?;
+
+        let req = self
+            .client
+            .request_with_model(Method::POST, &model, "generateContent")?
+            .json(&request_body);
+
+        let res = self.client.send(req).await?;
+        let res = error_for_status(res)
+            .await
+            .map_err(|e| anyhow!("TTS generation failed: {}", e))?;
+
+        let json: TtsResponse = res.json().await?;
+        self.client
+            .record_usage(&model, json.usage_metadata.as_ref())?;
+        let audio_part = json
+            .candidates
+            .and_then(|mut c| c.pop())
+            .and_then(|c| {
+                c.content.parts.into_iter().find_map(|part| match part {
+                    Part::InlineData { inline_data, .. } => Some(inline_data.data),
+                    _ => None,
+                })
+            })
+            .ok_or_else(|| anyhow!("No audio found in response"))?;
+
+        STANDARD
+            .decode(&audio_part)
+            .map_err(|e| anyhow!("Failed to decode audio: {}", e))
+    }
+
+    /// Streams the generated speech as raw PCM chunks while the model produces them.
+    ///
+    /// Uses `streamGenerateContent` with server-sent events, so every chunk can be written
+    /// to an audio sink or a file as soon as it arrives instead of waiting for the whole clip.
+    pub async fn stream(&self, params: TtsGen) -> Result<impl Stream<Item = Result<Vec<u8>>>> {
+        let model = params
+            .model
+            .clone()
+            .unwrap_or_else(|| self.client.get_model());
+        let request_body = Self::build_request(&model, &params)?;
+        self.client
+            .ensure_supports(&model, "generateContent")
+            .await?;
+        let tokens = estimate_tokens(
+            request_body
+                .contents
+                .iter()
+                .chain(&request_body.system_instruction),
+        );
+        self.client.throttle_tokens(&model, tokens).await?;
+
+        let req = self
+            .client
+            .request_with_model(Method::POST, &model, "streamGenerateContent")?
+            .query(&[("alt", "sse")])
+            .json(&request_body);
+
+        let res = self.client.send(req).await?;
+        let res = error_for_status(res)
+            .await
+            .map_err(|e| anyhow!("TTS generation failed: {}", e))?;
+
+        let client = self.client.clone();
+        Ok(sse_events(res)
+            .and_then(move |data| {
+                let client = client.clone();
+                let model = model.clone();
+                async move {
+                    let json: GeminiResponse = serde_json::from_str(&data)?;
+                    client.record_chunk_usage(&model, &json)?;
+                    let mut audio = Vec::new();
+
+                    for candidate in json.candidates.unwrap_or_default() {
+                        for part in candidate.content.parts {
+                            if let Part::InlineData { inline_data, .. } = part {
+                                let chunk = STANDARD
+                                    .decode(&inline_data.data)
+                                    .map_err(|e| anyhow!("Failed to decode audio: {}", e))?;
+                                audio.extend(chunk);
+                            }
+                        }
+                    }
+
+                    Ok(audio)
+                }
+            })
+            .try_filter(|audio| futures_util::future::ready(!audio.is_empty())))
+    }
+
+    fn build_request(model: &Model, params: &TtsGen) -> Result<TtsRequest> {
         let content = Content {
             parts: vec![params.input.to_part()],
+            role: None,
         };
 
         let prompt_text = content
             .parts
             .iter()
             .find_map(|part| {
-                if let Part::Text { text } = part {
+                if let Part::Text { text, .. } = part {
                     Some(text.clone())
                 } else {
                     None
@@ -48,14 +158,14 @@ impl Tts {
 
         let system_instruction = params.system.as_ref().map(|messages| Content {
             parts: messages.iter().map(|msg| msg.to_part()).collect(),
+            role: None,
         });
 
-        let request_body = TtsRequest {
-            model: params.model.to_string(),
+        Ok(TtsRequest {
+            model: model.to_string(),
             contents: vec![Content {
-                parts: vec![Part::Text {
-                    text: prompt_text.clone(),
-                }],
+                parts: vec![Part::text(&prompt_text)],
+                role: None,
             }],
             generation_config: TtsGenerationConfig {
                 response_modalities: vec!["AUDIO".into()],
@@ -68,38 +178,6 @@ impl Tts {
                 },
             },
             system_instruction,
-        };
-
-        let req = self
-            .client
-            .request(Method::POST, "generateContent")?
-            .json(&request_body);
-
-        let res = req.send().await?;
-
-        if !res.status().is_success() {
-            let body = res.text().await?;
-            let err = match serde_json::from_str::<ErrorWrapper>(&body) {
-                Ok(err_struct) => err_struct.error.message,
-                Err(_) => body,
-            };
-            return Err(anyhow!("TTS generation failed: {}", err));
-        }
-
-        let json: TtsResponse = res.json().await?;
-        let audio_part = json
-            .candidates
-            .and_then(|mut c| c.pop())
-            .and_then(|c| {
-                c.content.parts.into_iter().find_map(|part| match part {
-                    ResPart::Image { inline_data } => Some(inline_data.data),
-                    _ => None,
-                })
-            })
-            .ok_or_else(|| anyhow!("No audio found in response"))?;
-
-        STANDARD
-            .decode(&audio_part)
-            .map_err(|e| anyhow!("Failed to decode audio: {}", e))
+        })
     }
 }
@@ -16,10 +16,8 @@ use ratatui::{
 
 use crate::messages::Content;
 use crate::messages::Message;
-use crate::models::Model;
 use crate::stream::StreamBuilder;
 use crate::traits::CTrait;
-use crate::utils::extract_text_from_partial_json;
 use crate::Client;
 
 use futures_util::StreamExt;
@@ -87,6 +85,7 @@ struct App {
     chat_history: Vec<Line<'static>>,
     current_input: Option<CurrentInput>,
     scroll_chat: u16,
+    show_thoughts: bool,
 }
 
 impl Default for App {
@@ -101,6 +100,7 @@ impl Default for App {
             current_input: None,
             client: None,
             scroll_chat: 0,
+            show_thoughts: false,
         }
     }
 }
@@ -137,6 +137,7 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
             match app.input_mode {
                 InputMode::Normal => match key.code {
                     KeyCode::Char('q') => break,
+                    KeyCode::Char('t') => app.show_thoughts = !app.show_thoughts,
                     KeyCode::Char('e') => {
                         app.input_mode = InputMode::Editing;
 
@@ -152,10 +153,8 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
                     KeyCode::Left | KeyCode::Char('a') => {
                         app.selected_tab = app.selected_tab.previous()
                     }
-                    KeyCode::Up => {
-                        if app.scroll_chat > 0 {
-                            app.scroll_chat -= 1;
-                        }
+                    KeyCode::Up if app.scroll_chat > 0 => {
+                        app.scroll_chat -= 1;
                     }
                     KeyCode::Down => {
                         app.scroll_chat += 1;
@@ -180,37 +179,37 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
                                     .alignment(Alignment::Right);
                             app.chat_history.push(user_msg);
 
-                            let parameters = StreamBuilder::default()
-                                .model(Model::Flash20)
-                                .input(Message::User {
-                                    content: Content::Text(msg),
-                                    name: None,
-                                })
-                                .build()?;
-
-                            let response = app
-                                .client
-                                .clone()
-                                .unwrap_or_default()
-                                .stream()
-                                .generate(parameters)
-                                .await?;
-                            let mut stream = response.bytes_stream();
-                            while let Some(mut chunk) = stream.next().await {
-                                if let Ok(parsed_json) =
-                                    std::str::from_utf8(chunk.as_mut().unwrap())
-                                {
-                                    if let Some(text_value) =
-                                        extract_text_from_partial_json(parsed_json)
-                                    {
-                                        let lines: Vec<&str> = text_value
-                                            .split("\\n")
-                                            .flat_map(|s| s.split('\n'))
-                                            .collect();
-
-                                        for line in lines {
-                                            let cleaned_line = line.replace('\\', "");
+                            let mut builder = StreamBuilder::default();
+                            builder.input(Message::User {
+                                content: Content::Text(msg),
+                                name: None,
+                            });
+                            if app.show_thoughts {
+                                builder.include_thoughts(true);
+                            }
+                            let parameters = builder.build()?;
+
+                            let streaming = app.client.clone().unwrap_or_default().stream();
+                            let mut chunks = Box::pin(streaming.chunks(parameters).await?);
+                            while let Some(chunk) = chunks.next().await {
+                                match chunk {
+                                    Ok(chunk) => {
+                                        if let Some(thoughts) = chunk.thoughts() {
+                                            for line in thoughts.lines().filter(|l| !l.is_empty()) {
+                                                app.chat_history.push(
+                                                    Line::from(vec![Span::styled(
+                                                        format!("💭 {}", line),
+                                                        Style::default()
+                                                            .fg(Color::DarkGray)
+                                                            .add_modifier(Modifier::DIM),
+                                                    )])
+                                                    .alignment(Alignment::Left),
+                                                );
+                                            }
+                                        }
 
+                                        let text = chunk.text().unwrap_or_default();
+                                        for cleaned_line in text.lines() {
                                             match app.chat_history.last_mut() {
                                                 Some(last_msg)
                                                     if last_msg
@@ -234,7 +233,7 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
                                                                 Style::default()
                                                                     .fg(Color::LightBlue),
                                                             ),
-                                                            Span::raw(cleaned_line),
+                                                            Span::raw(cleaned_line.to_string()),
                                                         ])
                                                         .alignment(Alignment::Left),
                                                     );
@@ -244,14 +243,10 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
                                             thread::sleep(Duration::from_millis(50));
                                         }
                                     }
-                                } else {
-                                    app.chat_history.push(
-                                        format!(
-                                            "Failed to parse chunk: {:?}",
-                                            chunk.as_ref().unwrap()
-                                        )
-                                        .into(),
-                                    );
+                                    Err(e) => {
+                                        app.chat_history
+                                            .push(format!("Failed to parse chunk: {}", e).into());
+                                    }
                                 }
                             }
 
@@ -260,9 +255,19 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
                         Tab::Settings => {
                             let api_key = app.api_key.value().to_string();
                             let model = app.selected_model.value().to_string();
-                            let gemini_client = Client::builder().model(&model).build()?;
-                            gemini_client.set_api_key(api_key);
-                            app.client = Some(gemini_client);
+                            match Client::builder().model(&model).build() {
+                                Ok(gemini_client) => {
+                                    gemini_client.set_api_key(api_key);
+                                    app.client = Some(gemini_client);
+                                }
+                                Err(e) => app.chat_history.push(
+                                    Line::from(vec![Span::styled(
+                                        format!("⚠️ {}", e),
+                                        Style::default().fg(Color::Red),
+                                    )])
+                                    .alignment(Alignment::Left),
+                                ),
+                            }
                         }
                         _ => {}
                     },
@@ -459,7 +464,7 @@ fn ui(f: &mut Frame, app: &mut App) {
     let footer_layout = Layout::new(Direction::Vertical, [Max(1), Max(1), Max(1)]).split(chunks[2]);
 
     let top_footer = Line::raw(
-        "◄ ► or a/d: switch tabs | ↑ ↓: scroll | e: edit | Tab: next input | Esc: cancel | Enter: save | q: quit",
+        "◄ ► or a/d: switch tabs | ↑ ↓: scroll | e: edit | t: toggle thoughts | Tab: next input | Esc: cancel | Enter: save | q: quit",
     )
     .centered();
 
@@ -1,6 +1,10 @@
+use crate::responses::ErrorWrapper;
 use crate::responses::Part;
 use anyhow::{anyhow, Result};
 use base64::{engine::general_purpose::STANDARD, Engine as _};
+use futures_util::stream::{self, Stream, StreamExt};
+use reqwest::{Response, StatusCode, Url};
+use std::fmt;
 use std::fs::File;
 use std::io::Read;
 use std::io::Write;
@@ -101,13 +105,15 @@ pub fn load_and_encode_image(file_path: &str) -> Result<String> {
 
 pub fn extract_image_or_text(parts: &[Part]) -> Result<Vec<u8>> {
     if let Some(base64_data) = parts.iter().find_map(|part| match part {
-        Part::Image { inline_data } => Some(inline_data.data.clone()),
+        Part::InlineData { inline_data, .. } if inline_data.mime_type.starts_with("image/") => {
+            Some(inline_data.data.clone())
+        }
         _ => None,
     }) {
         let image_bytes = STANDARD.decode(&base64_data)?;
         Ok(image_bytes)
     } else if let Some(text) = parts.iter().find_map(|part| match part {
-        Part::Text { text } => Some(text.clone()),
+        Part::Text { text, .. } => Some(text.clone()),
         _ => None,
     }) {
         Err(anyhow!("Expected image but got only text: {}", text))
@@ -115,3 +121,116 @@ pub fn extract_image_or_text(parts: &[Part]) -> Result<Vec<u8>> {
         Err(anyhow!("No image or text found in response"))
     }
 }
+
+/// Turns a non-success HTTP response into an error carrying the API error message.
+///
+/// Successful responses are returned untouched. For failures the body is parsed as a
+/// Gemini `ErrorWrapper` when possible, falling back to the raw body otherwise.
+pub async fn error_for_status(res: Response) -> Result<Response> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+
+    let body = res.text().await?;
+    let message = match serde_json::from_str::<ErrorWrapper>(&body) {
+        Ok(wrapper) => wrapper.error.message,
+        Err(_) => body,
+    };
+    Err(HttpError { status, message }.into())
+}
+
+/// A non-success response of the Gemini API, see `error_for_status`.
+///
+/// Can be recovered from an `anyhow::Error` with `downcast_ref::<HttpError>()`.
+#[derive(Debug, Clone)]
+pub struct HttpError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "API returned error (status {}): {}",
+            self.status, self.message
+        )
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// Replaces the value of the `key` query parameter of `url`, so that it can be logged.
+pub fn redact_url(url: &mut Url) {
+    if !url.query_pairs().any(|(name, _)| name == "key") {
+        return;
+    }
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = if name == "key" {
+                "<redacted>".to_string()
+            } else {
+                value.into_owned()
+            };
+            (name.into_owned(), value)
+        })
+        .collect();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+}
+
+/// Splits a server-sent events response into the `data` payload of each event.
+///
+/// Multiple `data:` lines belonging to the same event are joined with a newline, and
+/// events without any data (comments, keep-alives) are skipped.
+///
+/// # Arguments
+///
+/// * `res` - A response obtained from an endpoint called with `alt=sse`.
+///
+/// # Returns
+///
+/// A stream yielding the data payload of every event as it arrives.
+pub fn sse_events(res: Response) -> impl Stream<Item = Result<String>> {
+    let bytes = res.bytes_stream().boxed();
+
+    stream::try_unfold(
+        (bytes, Vec::<u8>::new()),
+        |(mut bytes, mut buffer)| async move {
+            loop {
+                if let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+                    let event: Vec<u8> = buffer.drain(..end + 2).collect();
+                    if let Some(data) = parse_sse_event(&event) {
+                        return Ok(Some((data, (bytes, buffer))));
+                    }
+                    continue;
+                }
+
+                match bytes.next().await {
+                    Some(chunk) => buffer.extend(chunk?.iter().filter(|b| **b != b'\r')),
+                    None => {
+                        let data = parse_sse_event(&buffer);
+                        buffer.clear();
+                        return Ok(data.map(|data| (data, (bytes, buffer))));
+                    }
+                }
+            }
+        },
+    )
+}
+
+fn parse_sse_event(event: &[u8]) -> Option<String> {
+    let event = String::from_utf8_lossy(event);
+    let data = event
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<&str>>();
+
+    if data.is_empty() {
+        None
+    } else {
+        Some(data.join("\n"))
+    }
+}
@@ -1,6 +1,7 @@
 use crate::client::Client;
 use crate::messages::Message;
 use crate::models::Model;
+use crate::ratelimit::estimate_tokens;
 use crate::requests::{Content, Part, VideoGenRequest, VideoParameters, VideoPrompt};
 use crate::responses::{ErrorWrapper, OperationStatus, VideoGenResponse};
 use crate::traits::CTrait;
@@ -19,7 +20,8 @@ pub struct Videos {
 #[derive(Builder, Clone)]
 #[builder(setter(into))]
 pub struct VideoGen {
-    pub model: Model,
+    #[builder(setter(into, strip_option), default)]
+    pub model: Option<Model>,
     pub input: Message,
     #[builder(setter(into, strip_option), default)]
     pub system: Option<Vec<Message>>,
@@ -29,13 +31,14 @@ impl Videos {
     pub async fn generate(&self, params: VideoGen) -> Result<Vec<u8>> {
         let content = Content {
             parts: vec![params.input.to_part()],
+            role: None,
         };
 
         let prompt_text = content
             .parts
             .iter()
             .find_map(|part| {
-                if let Part::Text { text } = part {
+                if let Part::Text { text, .. } = part {
                     Some(text.clone())
                 } else {
                     None
@@ -43,6 +46,8 @@ impl Videos {
             })
             .ok_or_else(|| anyhow!("Prompt must contain text"))?;
 
+        let model = params.model.unwrap_or_else(|| self.client.get_model());
+
         let request_body = VideoGenRequest {
             instances: vec![VideoPrompt {
                 prompt: prompt_text,
@@ -53,12 +58,19 @@ impl Videos {
             },
         };
 
+        self.client
+            .ensure_supports(&model, "predictLongRunning")
+            .await?;
+        self.client
+            .throttle_tokens(&model, estimate_tokens([&content]))
+            .await?;
+
         let req = self
             .client
-            .request(Method::POST, "predictLongRunning")?
+            .request_with_model(Method::POST, &model, "predictLongRunning")?
             .json(&request_body);
 
-        let res = req.send().await?;
+        let res = self.client.send(req).await?;
         let status = res.status();
 
         if !status.is_success() {
@@ -80,8 +92,8 @@ impl Videos {
             .ok_or_else(|| anyhow!("Missing operation name"))?;
 
         for _ in 0..30 {
-            let poll_req = self.client.request(Method::GET, &op_name)?;
-            let poll_res = poll_req.send().await?;
+            let poll_req = self.client.resource_request(Method::GET, &op_name)?;
+            let poll_res = self.client.send(poll_req).await?;
             let status: OperationStatus = poll_res.json().await?;
 
             if status.done.unwrap_or(false) {
@@ -3,10 +3,11 @@ use crate::messages::Message;
 use crate::models::Model;
 use crate::requests::Content as ReqContent;
 use crate::requests::GeminiRequest;
-use crate::requests::ImageContent;
 use crate::requests::Part;
+use crate::requests::ThinkingConfig;
 use crate::responses::GeminiResponse;
 use crate::traits::CTrait;
+use crate::utils::error_for_status;
 use crate::Client;
 use anyhow::anyhow;
 use anyhow::Result;
@@ -21,10 +22,18 @@ pub struct Visions {
 #[derive(Builder, Clone)]
 #[builder(setter(into))]
 pub struct Vision {
+    #[builder(setter(into, strip_option), default)]
+    pub model: Option<Model>,
     pub input: Message,
     pub image: Message,
     #[builder(setter(into, strip_option), default)]
     pub system: Option<Vec<Message>>,
+    /// Maximum number of thinking tokens of thinking models.
+    #[builder(setter(into, strip_option), default)]
+    pub thinking_budget: Option<i32>,
+    /// Returns thought summaries along with the answer.
+    #[builder(setter(into, strip_option), default)]
+    pub include_thoughts: Option<bool>,
 }
 
 impl Visions {
@@ -33,46 +42,58 @@ impl Visions {
 
         let system_instruction = params.system.as_ref().map(|messages| ReqContent {
             parts: messages.iter().map(|msg| msg.to_part()).collect(),
+            role: None,
         });
 
-        let image_data = match &params.image {
-            Message::Tool { content } => content.clone(),
+        let (mime_type, image_data) = match &params.image {
+            Message::Tool { content } => ("image/jpeg".to_string(), content.clone()),
             Message::User { content, .. }
             | Message::System { content, .. }
-            | Message::Developer { content, .. } => match content {
-                Content::Text(data) => data.clone(),
+            | Message::Developer { content, .. }
+            | Message::Model { content } => match content {
+                Content::Text(data) => ("image/jpeg".to_string(), data.clone()),
+                Content::Image { mime_type, data } => (mime_type.clone(), data.clone()),
+                _ => return Err(anyhow!("The image must be text or image content")),
             },
         };
 
-        let image_part = Part::image(Some(ImageContent {
-            mime_type: "image/jpeg".to_string(),
-            data: image_data,
-        }));
+        let image_part = Part::inline_data(&mime_type, &image_data);
+
+        let model = params.model.unwrap_or_else(|| self.client.get_model());
 
         let request_body = GeminiRequest {
-            model: Model::Pro25Preview.to_string(),
+            model: model.to_string(),
             contents: vec![crate::requests::Content {
                 parts: vec![input_part, image_part],
+                role: None,
             }],
             system_instruction,
-            config: None,
+            config: ThinkingConfig::config(params.thinking_budget, params.include_thoughts),
+            tools: None,
+            cached_content: None,
         };
 
+        self.client
+            .ensure_supports(&model, "generateContent")
+            .await?;
+        self.client
+            .ensure_within_limit(&model, &request_body)
+            .await?;
+        self.client.throttle(&model, &request_body).await?;
+
         let req = self
             .client
-            .request(Method::POST, "generateContent")?
+            .request_with_model(Method::POST, &model, "generateContent")?
             .json(&request_body);
 
-        let res = req.send().await?;
+        let res = error_for_status(self.client.send(req).await?).await?;
         let json: GeminiResponse = res.json().await?;
+        self.client
+            .record_usage(&model, json.usage_metadata.as_ref())?;
 
-        let candidates = json
-            .candidates
-            .ok_or_else(|| anyhow!("Missing candidates"))?;
-
-        match &candidates[0].content.parts[0] {
-            Part::Text { text } => Ok(text.clone()),
-            _ => Err(anyhow!("Expected text response")),
+        if json.candidates.is_none() {
+            return Err(anyhow!("Missing candidates"));
         }
+        json.text().ok_or_else(|| anyhow!("Expected text response"))
     }
 }
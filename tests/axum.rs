@@ -0,0 +1,213 @@
+#![cfg(feature = "axum")]
+
+use anyhow::Result;
+use gems::auth::StaticToken;
+use gems::client::Backend;
+use gems::integrations::axum::router;
+use gems::traits::CTrait;
+use gems::Client;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+
+mod common;
+use common::{gemini, MockServer};
+
+async fn start(backend: &MockServer, api_key: Option<&str>) -> Result<String> {
+    let client = Client::builder().base_url(&backend.url).build()?;
+    if let Some(key) = api_key {
+        client.set_api_key(key.to_string());
+    }
+    serve(client).await
+}
+
+async fn serve(client: Client) -> Result<String> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}", listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, router(client)).await });
+    Ok(url)
+}
+
+#[tokio::test]
+async fn test_chat_count_and_embed() -> Result<()> {
+    let backend = MockServer::start(gemini).await;
+    let url = start(&backend, Some("server-key")).await?;
+    let http = reqwest::Client::new();
+    let chat = json!({
+        "model": "gemini-1.5-flash",
+        "messages": [{ "role": "user", "content": "Hi" }],
+        "system": [{ "role": "system", "content": "Be brief." }]
+    });
+
+    let response: Value = http
+        .post(format!("{}/chat", url))
+        .json(&chat)
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(
+        response["candidates"][0]["content"]["parts"][0]["text"],
+        "Hello!"
+    );
+
+    let count: Value = http
+        .post(format!("{}/tokens/count", url))
+        .json(&chat)
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(count["totalTokens"], 4);
+
+    let embedding: Value = http
+        .post(format!("{}/embeddings", url))
+        .json(&json!({ "input": { "role": "user", "content": "Hi" }, "task_type": "RETRIEVAL_QUERY" }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(embedding["embedding"]["values"], json!([0.5, 0.25]));
+
+    let requests = backend.requests();
+    assert!(requests[0]
+        .path
+        .contains("models/gemini-1.5-flash:generateContent"));
+    assert_eq!(
+        requests[0].json()["system_instruction"]["parts"][0]["text"],
+        "Be brief."
+    );
+    assert_eq!(requests[2].json()["taskType"], "RETRIEVAL_QUERY");
+    assert!(requests
+        .iter()
+        .all(|r| r.header("x-goog-api-key") == Some("server-key")));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chat_stream() -> Result<()> {
+    let backend = MockServer::start(gemini).await;
+    let url = start(&backend, Some("server-key")).await?;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/chat/stream", url))
+        .json(&json!({ "messages": [{ "role": "user", "content": "Hi" }] }))
+        .send()
+        .await?;
+    assert!(response.headers()["content-type"]
+        .to_str()?
+        .starts_with("text/event-stream"));
+
+    let body = response.text().await?;
+    let text: String = body
+        .lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .map(|data| serde_json::from_str::<Value>(data).unwrap())
+        .filter_map(|chunk| {
+            chunk["candidates"][0]["content"]["parts"][0]["text"]
+                .as_str()
+                .map(str::to_string)
+        })
+        .collect();
+    assert_eq!(text, "Hello");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_scoped_api_keys() -> Result<()> {
+    let backend = MockServer::start(gemini).await;
+    let url = start(&backend, None).await?;
+    let http = reqwest::Client::new();
+    let chat = json!({ "messages": [{ "role": "user", "content": "Hi" }] });
+
+    let missing = http
+        .post(format!("{}/chat", url))
+        .json(&chat)
+        .send()
+        .await?;
+    assert_eq!(missing.status(), 401);
+
+    http.post(format!("{}/chat", url))
+        .header("x-goog-api-key", "header-key")
+        .json(&chat)
+        .send()
+        .await?
+        .error_for_status()?;
+    http.post(format!("{}/chat", url))
+        .bearer_auth("bearer-key")
+        .json(&chat)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let requests = backend.requests();
+    assert_eq!(requests[0].header("x-goog-api-key"), Some("header-key"));
+    assert_eq!(requests[1].header("x-goog-api-key"), Some("bearer-key"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_error_status_mapping() -> Result<()> {
+    let backend = MockServer::start(gemini).await;
+    let url = start(&backend, Some("server-key")).await?;
+    let http = reqwest::Client::new();
+
+    let not_found = http
+        .post(format!("{}/chat", url))
+        .json(
+            &json!({ "model": "missing-model", "messages": [{ "role": "user", "content": "Hi" }] }),
+        )
+        .send()
+        .await?;
+    assert_eq!(not_found.status(), 404);
+    let body: Value = not_found.json().await?;
+    assert_eq!(body["error"]["code"], 404);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("is not found"));
+
+    let invalid_model = http
+        .get(format!("{}/models/not%20a%20model", url))
+        .send()
+        .await?;
+    assert_eq!(invalid_model.status(), 400);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_vertex_credentials_need_no_api_key() -> Result<()> {
+    let backend = MockServer::start(gemini).await;
+    let client = Client::builder()
+        .backend(Backend::VertexAi {
+            project: "my-project".to_string(),
+            location: "us-central1".to_string(),
+        })
+        .base_url(&backend.url)
+        .credentials(StaticToken("vertex-token".to_string()))
+        .build()?;
+    let url = serve(client).await?;
+
+    let response: Value = reqwest::Client::new()
+        .post(format!("{}/chat", url))
+        .json(&json!({ "messages": [{ "role": "user", "content": "Hi" }] }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(
+        response["candidates"][0]["content"]["parts"][0]["text"],
+        "Hello!"
+    );
+
+    let recorded = &backend.requests()[0];
+    assert!(recorded
+        .path
+        .starts_with("/projects/my-project/locations/us-central1/publishers/google/models/"));
+    assert_eq!(
+        recorded.header("authorization"),
+        Some("Bearer vertex-token")
+    );
+    assert_eq!(recorded.header("x-goog-api-key"), None);
+    Ok(())
+}
@@ -6,6 +6,12 @@ use gems::models::Model;
 use gems::traits::CTrait;
 use gems::Client;
 
+mod common;
+use common::MockServer;
+
+const CHAT_RESPONSE: &str =
+    r#"{"candidates": [{"content": {"parts": [{"text": "Hi!"}], "role": "model"}}]}"#;
+
 #[test]
 fn test_build_with_required() {
     let chat = ChatBuilder::default()
@@ -17,7 +23,7 @@ fn test_build_with_required() {
         .build()
         .unwrap();
 
-    assert_eq!(chat.model, Model::Flash20);
+    assert_eq!(chat.model, Some(Model::Flash20));
     assert_eq!(chat.messages.len(), 1);
     assert!(chat.system.is_none());
 }
@@ -41,7 +47,7 @@ fn test_build_with_system() {
         .build()
         .unwrap();
 
-    assert_eq!(chat.model, Model::Pro15);
+    assert_eq!(chat.model, Some(Model::Pro15));
     assert_eq!(chat.messages.len(), 1);
     assert_eq!(chat.system.as_ref().unwrap().len(), 1);
 }
@@ -260,3 +266,265 @@ async fn test_clone() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_request_model_drives_url() -> Result<()> {
+    let server = MockServer::start(|_| (200, CHAT_RESPONSE.to_string())).await;
+    let gemini_client = Client::builder()
+        .model(&Model::Flash20.to_string())
+        .base_url(&server.url)
+        .build()?;
+    gemini_client.set_api_key("test-key".to_string());
+
+    let message = Message::User {
+        content: Content::Text("Hello".into()),
+        name: None,
+    };
+
+    let with_model = ChatBuilder::default()
+        .model(Model::Pro15)
+        .messages(vec![message.clone()])
+        .build()?;
+    let without_model = ChatBuilder::default().messages(vec![message]).build()?;
+
+    let chats = gemini_client.chat();
+    let (first, second) = tokio::join!(chats.generate(with_model), chats.generate(without_model));
+    assert_eq!(first?, "Hi!");
+    assert_eq!(second?, "Hi!");
+
+    let mut paths: Vec<String> = server
+        .requests()
+        .iter()
+        .map(|request| request.path.split('?').next().unwrap().to_string())
+        .collect();
+    paths.sort();
+
+    assert_eq!(
+        paths,
+        vec![
+            "/models/gemini-1.5-pro:generateContent",
+            "/models/gemini-2.0-flash:generateContent",
+        ]
+    );
+    assert_eq!(gemini_client.get_model(), Model::Flash20);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_thinking_config_and_thoughts() -> Result<()> {
+    let server = MockServer::start(|_| {
+        (
+            200,
+            r#"{"candidates": [{"content": {"parts": [{"text": "Checking divisors up to 9.", "thought": true}, {"text": "Yes, 97 is prime."}], "role": "model"}}], "usageMetadata": {"promptTokenCount": 6, "candidatesTokenCount": 5, "thoughtsTokenCount": 120}}"#
+                .to_string(),
+        )
+    })
+    .await;
+    let gemini_client = Client::builder().base_url(&server.url).build()?;
+    gemini_client.set_api_key("test-key".to_string());
+
+    let params = ChatBuilder::default()
+        .model(Model::Pro25Preview)
+        .messages(vec![Message::User {
+            content: Content::Text("Is 97 prime?".into()),
+            name: None,
+        }])
+        .thinking_budget(1024)
+        .include_thoughts(true)
+        .build()?;
+
+    let response = gemini_client
+        .chat()
+        .generate_response(params.clone())
+        .await?;
+    assert_eq!(response.text().as_deref(), Some("Yes, 97 is prime."));
+    assert_eq!(
+        response.thoughts().as_deref(),
+        Some("Checking divisors up to 9.")
+    );
+    assert_eq!(
+        response.usage_metadata.unwrap().thoughts_token_count,
+        Some(120)
+    );
+
+    let answer = gemini_client.chat().generate(params).await?;
+    assert_eq!(answer, "Yes, 97 is prime.");
+
+    let body = server.requests()[0].json();
+    assert_eq!(
+        body["generationConfig"]["thinkingConfig"],
+        serde_json::json!({ "thinkingBudget": 1024, "includeThoughts": true })
+    );
+    assert!(body["generationConfig"].get("responseModalities").is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stream_chunks_separate_thoughts() -> Result<()> {
+    use futures_util::StreamExt;
+    use gems::stream::StreamBuilder;
+
+    let server = MockServer::start(|_| {
+        (
+            200,
+            concat!(
+                "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"Thinking...\", \"thought\": true}], \"role\": \"model\"}}]}\r\n\r\n",
+                "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"Hello\"}], \"role\": \"model\"}}]}\r\n\r\n",
+            )
+            .to_string(),
+        )
+    })
+    .await;
+    let gemini_client = Client::builder().base_url(&server.url).build()?;
+    gemini_client.set_api_key("test-key".to_string());
+
+    let params = StreamBuilder::default()
+        .input(Message::User {
+            content: Content::Text("Hi".into()),
+            name: None,
+        })
+        .include_thoughts(true)
+        .build()?;
+    let chunks: Vec<_> = gemini_client
+        .stream()
+        .chunks(params)
+        .await?
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(chunks.len(), 2);
+    let first = chunks[0].as_ref().unwrap();
+    assert_eq!(first.thoughts().as_deref(), Some("Thinking..."));
+    assert!(first.text().is_none());
+    assert_eq!(chunks[1].as_ref().unwrap().text().as_deref(), Some("Hello"));
+
+    let recorded = &server.requests()[0];
+    assert!(recorded.path.contains(":streamGenerateContent"));
+    assert!(recorded.path.contains("alt=sse"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_google_search_grounding_citations() -> Result<()> {
+    use gems::requests::Tool;
+
+    let server = MockServer::start(|_| {
+        (
+            200,
+            r#"{"candidates": [{"content": {"parts": [{"text": "Spain won Euro 2024. The final was in Berlin."}], "role": "model"}, "groundingMetadata": {"webSearchQueries": ["euro 2024 winner"], "searchEntryPoint": {"renderedContent": "<div></div>"}, "groundingChunks": [{"web": {"uri": "https://a.example", "title": "a.example"}}, {"web": {"uri": "https://b.example", "title": "b.example"}}], "groundingSupports": [{"segment": {"endIndex": 20, "text": "Spain won Euro 2024."}, "groundingChunkIndices": [0, 1], "confidenceScores": [0.9, 0.8]}, {"segment": {"startIndex": 21, "endIndex": 45, "text": "The final was in Berlin."}, "groundingChunkIndices": [1]}]}}]}"#
+                .to_string(),
+        )
+    })
+    .await;
+    let gemini_client = Client::builder().base_url(&server.url).build()?;
+    gemini_client.set_api_key("test-key".to_string());
+
+    let params = ChatBuilder::default()
+        .messages(vec![Message::User {
+            content: Content::Text("Who won Euro 2024?".into()),
+            name: None,
+        }])
+        .tools(vec![Tool::google_search(), Tool::url_context()])
+        .build()?;
+    let response = gemini_client.chat().generate_response(params).await?;
+
+    let metadata = response.grounding_metadata().unwrap();
+    assert_eq!(metadata.web_search_queries, vec!["euro 2024 winner"]);
+    assert_eq!(
+        metadata
+            .search_entry_point
+            .as_ref()
+            .and_then(|entry| entry.rendered_content.as_deref()),
+        Some("<div></div>")
+    );
+    assert_eq!(metadata.grounding_supports[1].segment.start_index, Some(21));
+    assert_eq!(metadata.sources().len(), 2);
+    assert_eq!(
+        response.cited_text().as_deref(),
+        Some("Spain won Euro 2024.[1][2] The final was in Berlin.[2]")
+    );
+
+    let body = server.requests()[0].json();
+    assert_eq!(
+        body["tools"],
+        serde_json::json!([{ "googleSearch": {} }, { "urlContext": {} }])
+    );
+    Ok(())
+}
+
+#[test]
+fn test_cite_skips_out_of_range_segments() {
+    use gems::responses::{GroundingMetadata, GroundingSupport, Segment};
+
+    let metadata = GroundingMetadata {
+        grounding_supports: vec![GroundingSupport {
+            segment: Segment {
+                end_index: Some(100),
+                ..Default::default()
+            },
+            grounding_chunk_indices: vec![0],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    assert_eq!(metadata.cite("Short answer."), "Short answer.");
+}
+
+#[test]
+fn test_cited_text_per_part() -> Result<()> {
+    let response: gems::responses::GeminiResponse = serde_json::from_str(
+        r#"{"candidates": [{"content": {"parts": [{"text": "Spain won."}, {"text": " Berlin hosted."}], "role": "model"}, "groundingMetadata": {"groundingChunks": [{"web": {"uri": "https://a.example"}}, {"web": {"uri": "https://b.example"}}], "groundingSupports": [{"segment": {"endIndex": 10}, "groundingChunkIndices": [0]}, {"segment": {"partIndex": 1, "startIndex": 1, "endIndex": 15}, "groundingChunkIndices": [1]}]}}]}"#,
+    )?;
+
+    assert_eq!(
+        response.cited_text().as_deref(),
+        Some("Spain won.[1] Berlin hosted.[2]")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_code_execution_parts() -> Result<()> {
+    use gems::requests::Tool;
+
+    let server = MockServer::start(|_| {
+        (
+            200,
+            r#"{"candidates": [{"content": {"parts": [{"text": "Let me compute it."}, {"executableCode": {"language": "PYTHON", "code": "print(sum(range(10)))\n"}}, {"codeExecutionResult": {"outcome": "OUTCOME_OK", "output": "45\n"}}, {"text": "The sum is 45."}], "role": "model"}}]}"#
+                .to_string(),
+        )
+    })
+    .await;
+    let gemini_client = Client::builder().base_url(&server.url).build()?;
+    gemini_client.set_api_key("test-key".to_string());
+
+    let params = ChatBuilder::default()
+        .messages(vec![Message::User {
+            content: Content::Text("Sum the numbers below 10.".into()),
+            name: None,
+        }])
+        .tools(vec![Tool::code_execution()])
+        .build()?;
+    let response = gemini_client.chat().generate_response(params).await?;
+
+    assert_eq!(
+        response.text().as_deref(),
+        Some("Let me compute it.The sum is 45.")
+    );
+    assert_eq!(
+        response.executable_code()[0].code,
+        "print(sum(range(10)))\n"
+    );
+    assert!(response.code_execution_results()[0].is_ok());
+    assert_eq!(
+        response.markdown().as_deref(),
+        Some(
+            "Let me compute it.\n\n```python\nprint(sum(range(10)))\n```\n\nOutput:\n```\n45\n```\n\nThe sum is 45."
+        )
+    );
+
+    let body = server.requests()[0].json();
+    assert_eq!(body["tools"], serde_json::json!([{ "codeExecution": {} }]));
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use anyhow::Result;
+use gems::client::Proxy;
+use gems::traits::CTrait;
+use gems::Client;
+use std::time::Duration;
+
+mod common;
+use common::{gemini, hello, MockServer};
+
+#[tokio::test]
+async fn test_api_key_header() -> Result<()> {
+    let server = MockServer::start(gemini).await;
+    let client = Client::builder().base_url(&server.url).build()?;
+    client.set_api_key("secret-key".to_string());
+
+    client.chat().generate(hello()?).await?;
+
+    let recorded = &server.requests()[0];
+    assert_eq!(recorded.header("x-goog-api-key"), Some("secret-key"));
+    assert!(!recorded.path.contains("secret-key"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_api_key_in_query() -> Result<()> {
+    let server = MockServer::start(gemini).await;
+    let client = Client::builder()
+        .base_url(&server.url)
+        .api_key_in_query(true)
+        .build()?;
+    client.set_api_key("secret-key".to_string());
+
+    client.chat().generate(hello()?).await?;
+
+    let recorded = &server.requests()[0];
+    assert!(recorded.path.ends_with("?key=secret-key"));
+    assert_eq!(recorded.header("x-goog-api-key"), None);
+    Ok(())
+}
+
+#[test]
+fn test_debug_redacts_api_key() -> Result<()> {
+    let client = Client::builder().build()?;
+    assert!(format!("{:?}", client).contains("api_key: None"));
+
+    client.set_api_key("secret-key".to_string());
+    let debug = format!("{:?}", client);
+    assert!(!debug.contains("secret-key"));
+    assert!(debug.contains("<redacted>"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_errors_redact_api_key() -> Result<()> {
+    // Nothing listens on the port of a dropped listener, so the request fails to connect.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}", listener.local_addr()?);
+    drop(listener);
+
+    let client = Client::builder()
+        .base_url(&url)
+        .api_key_in_query(true)
+        .build()?;
+    client.set_api_key("secret-key".to_string());
+
+    let error = client.chat().generate(hello()?).await.unwrap_err();
+    let message = format!("{:#} {:?}", error, error);
+    assert!(!message.contains("secret-key"), "{}", message);
+    assert!(message.contains("key=%3Credacted%3E"), "{}", message);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_user_agent_and_proxy() -> Result<()> {
+    let proxy = MockServer::start(gemini).await;
+    let client = Client::builder()
+        .base_url("http://gemini.invalid/v1beta")
+        .proxy(Proxy::http(&proxy.url)?)
+        .user_agent("my-app/1.0")
+        .build()?;
+    client.set_api_key("secret-key".to_string());
+
+    client.chat().generate(hello()?).await?;
+
+    let recorded = &proxy.requests()[0];
+    assert!(recorded
+        .path
+        .starts_with("http://gemini.invalid/v1beta/models/"));
+    assert_eq!(recorded.header("user-agent"), Some("my-app/1.0"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_custom_http_client() -> Result<()> {
+    let server = MockServer::start(gemini).await;
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("x-team", "search".parse()?);
+    let http = reqwest::Client::builder()
+        .default_headers(headers)
+        .build()?;
+
+    let client = Client::builder()
+        .base_url(&server.url)
+        .http_client(http.clone())
+        .build()?;
+    client.set_api_key("secret-key".to_string());
+    client.chat().generate(hello()?).await?;
+    assert_eq!(server.requests()[0].header("x-team"), Some("search"));
+
+    let conflicting = Client::builder()
+        .http_client(http)
+        .timeout(Duration::from_secs(1))
+        .build();
+    assert!(conflicting.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_timeout() -> Result<()> {
+    // Accepts connections but never answers.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}", listener.local_addr()?);
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            connections.push(stream);
+        }
+    });
+
+    let client = Client::builder()
+        .base_url(&url)
+        .timeout(Duration::from_millis(200))
+        .build()?;
+    client.set_api_key("secret-key".to_string());
+
+    let error = client.chat().generate(hello()?).await.unwrap_err();
+    let error = error.downcast_ref::<reqwest::Error>().unwrap();
+    assert!(error.is_timeout());
+    Ok(())
+}
@@ -0,0 +1,183 @@
+#![allow(dead_code)]
+
+use gems::chat::{Chat, ChatBuilder};
+use gems::messages::{Content, Message};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// A request received by the mock server.
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Recorded {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).unwrap()
+    }
+}
+
+/// Answers like the Gemini API: generation, streaming, token counting, embeddings and
+/// model listing, with a 404 for `models/missing-model`.
+pub fn gemini(request: &Recorded) -> (u16, String) {
+    if request.path.contains("models/missing-model") {
+        return (
+            404,
+            r#"{"error": {"code": 404, "message": "models/missing-model is not found"}}"#.into(),
+        );
+    }
+    let body = if request.path.contains(":streamGenerateContent") {
+        concat!(
+            "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"Hel\"}], \"role\": \"model\"}}]}\r\n\r\n",
+            "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"lo\"}], \"role\": \"model\"}, \"finishReason\": \"STOP\"}]}\r\n\r\n",
+        )
+    } else if request.path.contains(":generateContent") {
+        r#"{"candidates": [{"content": {"parts": [{"text": "Hello!"}], "role": "model"}, "finishReason": "MAX_TOKENS"}], "usageMetadata": {"promptTokenCount": 5, "candidatesTokenCount": 2}}"#
+    } else if request.path.contains(":countTokens") {
+        r#"{"totalTokens": 4}"#
+    } else if request.path.contains(":batchEmbedContents") {
+        r#"{"embeddings": [{"values": [0.1, 0.2]}, {"values": [0.3, 0.4]}]}"#
+    } else if request.path.contains(":embedContent") {
+        r#"{"embedding": {"values": [0.5, 0.25]}}"#
+    } else {
+        r#"{"models": [{"name": "models/gemini-2.0-flash", "version": "001", "displayName": "Gemini 2.0 Flash", "inputTokenLimit": 1048576, "outputTokenLimit": 8192, "supportedGenerationMethods": ["generateContent"]}]}"#
+    };
+    (200, body.to_string())
+}
+
+/// A chat of a single "Hi" from the user.
+pub fn hello() -> anyhow::Result<Chat> {
+    Ok(ChatBuilder::default()
+        .messages(vec![Message::User {
+            content: Content::Text("Hi".to_string()),
+            name: None,
+        }])
+        .build()?)
+}
+
+/// A minimal HTTP/1.1 server answering every request with the given handler.
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Recorded>>>,
+}
+
+impl MockServer {
+    pub async fn start<F>(handler: F) -> MockServer
+    where
+        F: Fn(&Recorded) -> (u16, String) + Send + Sync + 'static,
+    {
+        Self::start_chunked(usize::MAX, handler).await
+    }
+
+    /// Like `start`, but writes response bodies in pieces of `size` bytes, so that clients
+    /// receive them over several reads.
+    pub async fn start_chunked<F>(size: usize, handler: F) -> MockServer
+    where
+        F: Fn(&Recorded) -> (u16, String) + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let handler = Arc::new(handler);
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(_) => break,
+                };
+                let recorded = recorded.clone();
+                let handler = handler.clone();
+
+                tokio::spawn(async move {
+                    let Some(request) = read_request(&mut stream).await else {
+                        return;
+                    };
+                    let (status, body) = handler(&request);
+                    recorded.lock().unwrap().push(request);
+
+                    let head = format!(
+                        "HTTP/1.1 {} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        status,
+                        body.len()
+                    );
+                    let _ = stream.write_all(head.as_bytes()).await;
+                    for piece in body.as_bytes().chunks(size) {
+                        let _ = stream.write_all(piece).await;
+                        let _ = stream.flush().await;
+                        if piece.len() == size {
+                            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+                        }
+                    }
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+
+        MockServer { url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<Recorded> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn read_request(stream: &mut tokio::net::TcpStream) -> Option<Recorded> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let mut body = buffer[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+
+    Some(Recorded {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
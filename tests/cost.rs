@@ -0,0 +1,159 @@
+mod common;
+
+use anyhow::Result;
+use common::MockServer;
+use futures_util::TryStreamExt;
+use gems::chat::ChatBuilder;
+use gems::cost::{estimate, load_log, Pricing, UsageSummary, UsageTracker};
+use gems::messages::{Content, Message};
+use gems::models::Model;
+use gems::responses::UsageMetadata;
+use gems::stream::StreamBuilder;
+use gems::traits::CTrait;
+use gems::Client;
+use std::str::FromStr;
+
+fn usage(prompt: i32, cached: i32, output: i32, thoughts: i32) -> UsageMetadata {
+    UsageMetadata {
+        prompt_token_count: Some(prompt),
+        cached_content_token_count: Some(cached),
+        candidates_token_count: Some(output),
+        thoughts_token_count: Some(thoughts),
+        ..Default::default()
+    }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-12,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn test_estimate_splits_cached_and_thinking_tokens() {
+    let pricing = Pricing::new(1.0, 0.25, 4.0);
+    let cost = estimate(&pricing, &usage(1_000_000, 400_000, 100_000, 150_000));
+
+    assert_close(cost.input, 0.6);
+    assert_close(cost.cached_input, 0.1);
+    assert_close(cost.output, 1.0);
+    assert_close(cost.total(), 1.7);
+}
+
+#[test]
+fn test_tracker_summarizes_per_label_and_model() -> Result<()> {
+    let tracker = UsageTracker::new();
+    let search = tracker.labeled("search");
+    let custom = Model::from_str("my-model")?;
+
+    search.record(&Model::Flash20, &usage(1000, 0, 500, 0))?;
+    search.record(&Model::Flash20, &usage(1000, 0, 500, 0))?;
+    tracker
+        .labeled("chat")
+        .record(&custom, &usage(10, 0, 5, 0))?;
+    tracker.set_pricing(custom.clone(), Pricing::new(1.0, 1.0, 1.0));
+    tracker
+        .labeled("chat")
+        .record(&custom, &usage(10, 0, 5, 0))?;
+
+    let summary = tracker.summary();
+    assert_eq!(summary.rows.len(), 2);
+
+    let chat = &summary.rows[0];
+    assert_eq!(chat.label.as_deref(), Some("chat"));
+    assert_eq!(chat.calls, 2);
+    assert_eq!(chat.unpriced_calls, 1);
+    assert_close(chat.cost, 15.0 / 1_000_000.0);
+
+    let search = &summary.rows[1];
+    assert_eq!(search.model, "gemini-2.0-flash");
+    assert_eq!(search.prompt_tokens, 2000);
+    assert_close(
+        search.cost,
+        2.0 * (1000.0 * 0.10 + 500.0 * 0.40) / 1_000_000.0,
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_client_records_chat_usage_to_log() -> Result<()> {
+    let server = MockServer::start(|_| {
+        (
+            200,
+            r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "Hi"}]}}], "usageMetadata": {"promptTokenCount": 4, "candidatesTokenCount": 2, "totalTokenCount": 6}}"#
+                .to_string(),
+        )
+    })
+    .await;
+    let log = std::env::temp_dir().join(format!("gems-usage-{}.jsonl", std::process::id()));
+    let tracker = UsageTracker::new().with_log(&log);
+
+    let gemini_client = Client::builder().base_url(&server.url).build()?;
+    gemini_client.set_api_key("test-key".to_string());
+    let greeter = gemini_client.with_usage_tracker(tracker.labeled("greeter"));
+
+    let params = ChatBuilder::default()
+        .messages(vec![Message::User {
+            content: Content::Text("Hello".into()),
+            name: None,
+        }])
+        .build()?;
+    greeter.chat().generate(params.clone()).await?;
+    gemini_client.chat().generate(params).await?;
+
+    let records = tracker.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].label.as_deref(), Some("greeter"));
+    assert_eq!(records[0].prompt_tokens, 4);
+    assert_eq!(records[0].output_tokens, 2);
+
+    let logged = load_log(&log)?;
+    std::fs::remove_file(&log)?;
+    assert_eq!(logged, records);
+    assert_eq!(UsageSummary::from_records(&logged), tracker.summary());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_client_records_stream_usage() -> Result<()> {
+    let server = MockServer::start(|_| {
+        (
+            200,
+            concat!(
+                "data: {\"candidates\": [{\"content\": {\"role\": \"model\", \"parts\": [{\"text\": \"Hel\"}]}}], \"usageMetadata\": {\"promptTokenCount\": 4, \"candidatesTokenCount\": 1}}\r\n\r\n",
+                "data: {\"candidates\": [{\"content\": {\"role\": \"model\", \"parts\": [{\"text\": \"lo\"}]}, \"finishReason\": \"STOP\"}], \"usageMetadata\": {\"promptTokenCount\": 4, \"candidatesTokenCount\": 2}}\r\n\r\n",
+            )
+            .to_string(),
+        )
+    })
+    .await;
+    let tracker = UsageTracker::new();
+    let gemini_client = Client::builder()
+        .base_url(&server.url)
+        .usage_tracker(tracker.clone())
+        .build()?;
+    gemini_client.set_api_key("test-key".to_string());
+
+    let params = StreamBuilder::default()
+        .input(Message::User {
+            content: Content::Text("Hello".into()),
+            name: None,
+        })
+        .build()?;
+    let chunks: Vec<_> = gemini_client
+        .stream()
+        .chunks(params)
+        .await?
+        .try_collect()
+        .await?;
+    assert_eq!(chunks.len(), 2);
+
+    let records = tracker.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].prompt_tokens, 4);
+    assert_eq!(records[0].output_tokens, 2);
+    Ok(())
+}
@@ -1,9 +1,16 @@
 use anyhow::Result;
-use gems::embed::{BatchEmbeddingBuilder, EmbeddingBuilder};
+use gems::embed::cache::{DiskCache, EmbeddingCache, MemoryCache};
+use gems::embed::{BatchEmbedError, BatchEmbeddingBuilder, EmbeddingBuilder};
 use gems::messages::{Content, Message};
 use gems::models::Model;
+use gems::requests::TaskType;
+use gems::responses::Embedding as ResEmbedding;
 use gems::traits::CTrait;
 use gems::Client;
+use std::str::FromStr;
+
+mod common;
+use common::MockServer;
 
 #[test]
 fn test_embed_build_required() {
@@ -18,7 +25,7 @@ fn test_embed_build_required() {
         .build()
         .unwrap();
 
-    assert_eq!(embed.model, Model::Embedding);
+    assert_eq!(embed.model, Some(Model::Embedding));
     assert_eq!(embed.input, message);
 }
 
@@ -41,7 +48,7 @@ fn test_batch_embed_build() {
         .build()
         .unwrap();
 
-    assert_eq!(batch.model, Model::Embedding);
+    assert_eq!(batch.model, Some(Model::Embedding));
     assert_eq!(batch.input, inputs);
 }
 
@@ -123,3 +130,339 @@ async fn test_embed_batch() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_embed_build_task_options() {
+    let embed = EmbeddingBuilder::default()
+        .input(Message::User {
+            content: Content::Text("A magic backpack.".into()),
+            name: None,
+        })
+        .task_type(TaskType::RetrievalDocument)
+        .title("Backpacks")
+        .output_dimensionality(256u32)
+        .build()
+        .unwrap();
+
+    assert_eq!(embed.task_type, Some(TaskType::RetrievalDocument));
+    assert_eq!(embed.title.as_deref(), Some("Backpacks"));
+    assert_eq!(embed.output_dimensionality, Some(256));
+}
+
+#[test]
+fn test_task_type_from_str() {
+    assert_eq!(
+        TaskType::from_str("RETRIEVAL_QUERY").unwrap(),
+        TaskType::RetrievalQuery
+    );
+    assert_eq!(
+        TaskType::from_str("code-retrieval-query").unwrap(),
+        TaskType::CodeRetrievalQuery
+    );
+    assert!(TaskType::from_str("SUMMARIZATION").is_err());
+}
+
+#[tokio::test]
+async fn test_embed_sends_task_options() -> Result<()> {
+    let server = MockServer::start(|request| {
+        let body = if request.path.contains(":batchEmbedContents") {
+            r#"{"embeddings": [{"values": [0.1]}, {"values": [0.2]}]}"#
+        } else {
+            r#"{"embedding": {"values": [0.1, 0.2]}}"#
+        };
+        (200, body.to_string())
+    })
+    .await;
+    let gemini_client = Client::builder().base_url(&server.url).build()?;
+    gemini_client.set_api_key("test-key".to_string());
+
+    let params = EmbeddingBuilder::default()
+        .model(Model::Embedding)
+        .input(Message::User {
+            content: Content::Text("What is Rust?".into()),
+            name: None,
+        })
+        .task_type(TaskType::RetrievalQuery)
+        .output_dimensionality(2u32)
+        .build()?;
+    gemini_client.embeddings().create(params).await?;
+
+    let batch = BatchEmbeddingBuilder::default()
+        .model(Model::Embedding)
+        .input(vec![
+            Message::User {
+                content: Content::Text("First".into()),
+                name: None,
+            },
+            Message::User {
+                content: Content::Text("Second".into()),
+                name: None,
+            },
+        ])
+        .task_type(TaskType::RetrievalDocument)
+        .title("Notes")
+        .build()?;
+    gemini_client.embeddings().batch(batch).await?;
+
+    let requests = server.requests();
+    let single = requests
+        .iter()
+        .find(|r| r.path.contains(":embedContent"))
+        .unwrap()
+        .json();
+    assert_eq!(single["taskType"], "RETRIEVAL_QUERY");
+    assert_eq!(single["outputDimensionality"], 2);
+    assert!(single.get("title").is_none());
+
+    let batched = requests
+        .iter()
+        .find(|r| r.path.contains(":batchEmbedContents"))
+        .unwrap()
+        .json();
+    for request in batched["requests"].as_array().unwrap() {
+        assert_eq!(request["model"], "models/text-embedding-004");
+        assert_eq!(request["taskType"], "RETRIEVAL_DOCUMENT");
+        assert_eq!(request["title"], "Notes");
+    }
+
+    Ok(())
+}
+
+fn numbered_inputs(count: usize) -> Vec<Message> {
+    (0..count)
+        .map(|i| Message::User {
+            content: Content::Text(format!("item-{}", i)),
+            name: None,
+        })
+        .collect()
+}
+
+async fn numbering_server(failing_item: Option<&'static str>) -> MockServer {
+    MockServer::start(move |request| {
+        let json = request.json();
+        let requests = json["requests"].as_array().unwrap();
+        let texts: Vec<&str> = requests
+            .iter()
+            .map(|r| r["content"]["parts"][0]["text"].as_str().unwrap())
+            .collect();
+
+        if failing_item.is_some_and(|item| texts.contains(&item)) {
+            return (
+                500,
+                r#"{"error": {"message": "Internal error"}}"#.to_string(),
+            );
+        }
+
+        let embeddings: Vec<serde_json::Value> = texts
+            .iter()
+            .map(|text| {
+                let index: f64 = text.trim_start_matches("item-").parse().unwrap();
+                serde_json::json!({ "values": [index] })
+            })
+            .collect();
+        (
+            200,
+            serde_json::json!({ "embeddings": embeddings }).to_string(),
+        )
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_embed_batch_chunks_preserve_order() -> Result<()> {
+    let server = numbering_server(None).await;
+    let gemini_client = Client::builder().base_url(&server.url).build()?;
+    gemini_client.set_api_key("test-key".to_string());
+
+    let params = BatchEmbeddingBuilder::default()
+        .model(Model::Embedding)
+        .input(numbered_inputs(250))
+        .concurrency(3usize)
+        .build()?;
+    let response = gemini_client.embeddings().batch(params).await?;
+
+    let values: Vec<f64> = response
+        .embeddings
+        .unwrap()
+        .iter()
+        .map(|e| e.values[0])
+        .collect();
+    let expected: Vec<f64> = (0..250).map(|i| i as f64).collect();
+    assert_eq!(values, expected);
+
+    let mut sizes: Vec<usize> = server
+        .requests()
+        .iter()
+        .map(|r| r.json()["requests"].as_array().unwrap().len())
+        .collect();
+    sizes.sort();
+    assert_eq!(sizes, vec![50, 100, 100]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_embed_batch_reports_partial_failure() -> Result<()> {
+    let server = numbering_server(Some("item-5")).await;
+    let gemini_client = Client::builder().base_url(&server.url).build()?;
+    gemini_client.set_api_key("test-key".to_string());
+
+    let params = BatchEmbeddingBuilder::default()
+        .model(Model::Embedding)
+        .input(numbered_inputs(10))
+        .chunk_size(4usize)
+        .build()?;
+    let err = gemini_client.embeddings().batch(params).await.unwrap_err();
+
+    let batch_err = err.downcast_ref::<BatchEmbedError>().unwrap();
+    assert_eq!(batch_err.failures.len(), 1);
+    assert_eq!(batch_err.failures[0].range, 4..8);
+    assert!(batch_err.failures[0]
+        .error
+        .to_string()
+        .contains("Internal error"));
+
+    let present: Vec<bool> = batch_err.embeddings.iter().map(|e| e.is_some()).collect();
+    assert_eq!(
+        present,
+        vec![true, true, true, true, false, false, false, false, true, true]
+    );
+    assert_eq!(batch_err.embeddings[9].as_ref().unwrap().values, vec![9.0]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_embed_batch_cache_only_sends_misses() -> Result<()> {
+    let server = numbering_server(None).await;
+    let gemini_client = Client::builder()
+        .base_url(&server.url)
+        .embedding_cache(MemoryCache::new(100))
+        .build()?;
+    gemini_client.set_api_key("test-key".to_string());
+
+    let batch = |count| {
+        BatchEmbeddingBuilder::default()
+            .model(Model::Embedding)
+            .input(numbered_inputs(count))
+            .build()
+            .unwrap()
+    };
+    gemini_client.embeddings().batch(batch(3)).await?;
+    let response = gemini_client.embeddings().batch(batch(5)).await?;
+
+    let values: Vec<f64> = response
+        .embeddings
+        .unwrap()
+        .iter()
+        .map(|e| e.values[0])
+        .collect();
+    assert_eq!(values, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    let sent = requests[1].json();
+    let texts: Vec<&str> = sent["requests"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["content"]["parts"][0]["text"].as_str().unwrap())
+        .collect();
+    assert_eq!(texts, vec!["item-3", "item-4"]);
+
+    gemini_client.embeddings().batch(batch(5)).await?;
+    assert_eq!(server.requests().len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_embed_create_disk_cache() -> Result<()> {
+    let server =
+        MockServer::start(|_| (200, r#"{"embedding": {"values": [0.5]}}"#.to_string())).await;
+    let dir = std::env::temp_dir().join(format!("gems-cache-{}", std::process::id()));
+
+    let embed = |task_type| {
+        EmbeddingBuilder::default()
+            .model(Model::Embedding)
+            .input(Message::User {
+                content: Content::Text("Cache me".into()),
+                name: None,
+            })
+            .task_type(task_type)
+            .build()
+            .unwrap()
+    };
+
+    for _ in 0..2 {
+        // A fresh client per run, like separate invocations sharing the directory.
+        let gemini_client = Client::builder()
+            .base_url(&server.url)
+            .embedding_cache(DiskCache::new(&dir)?)
+            .build()?;
+        gemini_client.set_api_key("test-key".to_string());
+
+        let response = gemini_client
+            .embeddings()
+            .create(embed(TaskType::RetrievalDocument))
+            .await?;
+        assert_eq!(response.embedding.unwrap().values, vec![0.5]);
+    }
+    assert_eq!(server.requests().len(), 1);
+
+    let gemini_client = Client::builder()
+        .base_url(&server.url)
+        .embedding_cache(DiskCache::new(&dir)?)
+        .build()?;
+    gemini_client.set_api_key("test-key".to_string());
+    gemini_client
+        .embeddings()
+        .create(embed(TaskType::RetrievalQuery))
+        .await?;
+    assert_eq!(server.requests().len(), 2);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_disk_cache_concurrent_puts() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("gems-cache-puts-{}", std::process::id()));
+    let cache = DiskCache::new(&dir)?;
+    let embedding = ResEmbedding {
+        values: vec![0.25, 0.5],
+    };
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..8)
+            .map(|_| scope.spawn(|| (0..20).try_for_each(|_| cache.put("same-key", &embedding))))
+            .collect();
+        handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().unwrap())
+    })?;
+
+    assert_eq!(cache.get("same-key")?.unwrap().values, vec![0.25, 0.5]);
+    assert_eq!(std::fs::read_dir(&dir)?.count(), 1);
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_memory_cache_evicts_least_recently_used() -> Result<()> {
+    let cache = MemoryCache::new(2);
+    let embedding = |value| ResEmbedding {
+        values: vec![value],
+    };
+
+    cache.put("a", &embedding(1.0))?;
+    cache.put("b", &embedding(2.0))?;
+    assert!(cache.get("a")?.is_some());
+    cache.put("c", &embedding(3.0))?;
+
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get("b")?.is_none());
+    assert_eq!(cache.get("a")?, Some(embedding(1.0)));
+    assert_eq!(cache.get("c")?, Some(embedding(3.0)));
+    Ok(())
+}
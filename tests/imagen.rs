@@ -18,7 +18,7 @@ fn test_imagegen_build_required() {
         .build()
         .unwrap();
 
-    assert_eq!(imagegen.model, Model::FlashExpImage);
+    assert_eq!(imagegen.model, Some(Model::FlashExpImage));
     assert_eq!(imagegen.input, user_msg);
     assert!(imagegen.system.is_none());
 }
@@ -42,7 +42,7 @@ fn test_imagegen_build_with_system() {
         .build()
         .unwrap();
 
-    assert_eq!(imagegen.model, Model::FlashExpImage);
+    assert_eq!(imagegen.model, Some(Model::FlashExpImage));
     assert_eq!(imagegen.input, user_msg);
     assert_eq!(imagegen.system.unwrap().len(), 1);
 }
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use gems::embed::index::{cosine_similarity, normalize, Metric, VectorIndex};
+use gems::responses::{BatchEmbedContentsResponse, Embedding};
+use serde_json::{json, Value};
+
+fn embedding(values: &[f64]) -> Embedding {
+    Embedding {
+        values: values.to_vec(),
+    }
+}
+
+fn sample_index() -> VectorIndex {
+    let mut index = VectorIndex::new();
+    index
+        .add("x", embedding(&[1.0, 0.0]), json!({ "label": "x" }))
+        .unwrap();
+    index
+        .add("y", embedding(&[0.0, 1.0]), json!({ "label": "y" }))
+        .unwrap();
+    index
+        .add("xy", embedding(&[3.0, 3.0]), json!({ "label": "xy" }))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_search_by_metric() {
+    let index = sample_index();
+
+    let cosine = index.search(&[1.0, 0.1], 2, Metric::Cosine);
+    assert_eq!(cosine[0].id, "x");
+    assert_eq!(cosine[1].id, "xy");
+    assert_eq!(cosine[0].metadata["label"], "x");
+
+    let dot = index.search(&[1.0, 0.1], 1, Metric::DotProduct);
+    assert_eq!(dot[0].id, "xy");
+
+    let euclidean = index.search(&[0.1, 0.9], 3, Metric::Euclidean);
+    assert_eq!(euclidean[0].id, "y");
+    assert_eq!(euclidean[2].id, "xy");
+    assert!(euclidean[0].score < euclidean[1].score);
+}
+
+#[test]
+fn test_add_replaces_and_checks_dimensions() {
+    let mut index = sample_index();
+
+    index.add("x", embedding(&[0.5, 0.5]), Value::Null).unwrap();
+    assert_eq!(index.len(), 3);
+    assert_eq!(index.get("x").unwrap().values, vec![0.5, 0.5]);
+
+    assert!(index
+        .add("z", embedding(&[1.0, 2.0, 3.0]), Value::Null)
+        .is_err());
+    assert!(index.remove("x").is_some());
+    assert!(index.get("x").is_none());
+}
+
+#[test]
+fn test_add_batch_response() {
+    let mut index = VectorIndex::new();
+    let response = BatchEmbedContentsResponse {
+        embeddings: Some(vec![embedding(&[1.0, 0.0]), embedding(&[0.0, 1.0])]),
+    };
+
+    index
+        .add_batch(
+            vec![
+                ("a".to_string(), Value::Null),
+                ("b".to_string(), Value::Null),
+            ],
+            response,
+        )
+        .unwrap();
+    assert_eq!(index.dimensions(), Some(2));
+    assert_eq!(index.search(&[0.0, 2.0], 1, Metric::Cosine)[0].id, "b");
+
+    let mismatched = BatchEmbedContentsResponse {
+        embeddings: Some(vec![embedding(&[1.0, 0.0])]),
+    };
+    assert!(index.add_batch(Vec::new(), mismatched).is_err());
+}
+
+#[test]
+fn test_save_and_load() -> Result<()> {
+    let index = sample_index();
+    let path = std::env::temp_dir().join(format!("gems-index-{}.json", std::process::id()));
+
+    index.save(&path)?;
+    let loaded = VectorIndex::load(&path)?;
+    std::fs::remove_file(&path)?;
+
+    assert_eq!(loaded.entries(), index.entries());
+    Ok(())
+}
+
+#[test]
+fn test_vector_helpers() {
+    let mut values = vec![3.0, 4.0];
+    normalize(&mut values);
+    assert_eq!(values, vec![0.6, 0.8]);
+
+    assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 0.0]), 0.0);
+    assert!((cosine_similarity(&[1.0, 1.0], &[2.0, 2.0]) - 1.0).abs() < 1e-12);
+}
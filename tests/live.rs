@@ -0,0 +1,239 @@
+use anyhow::Result;
+use futures_util::{SinkExt, StreamExt};
+use gems::live::{LiveBuilder, LiveEvent};
+use gems::models::Model;
+use gems::requests::{FunctionDeclaration, FunctionResponse, Tool};
+use gems::traits::CTrait;
+use gems::Client;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::{accept_async, accept_hdr_async, WebSocketStream};
+
+type ServerSocket = WebSocketStream<tokio::net::TcpStream>;
+
+async fn read_json(socket: &mut ServerSocket) -> Value {
+    let message = socket.next().await.unwrap().unwrap();
+    serde_json::from_str(message.to_text().unwrap()).unwrap()
+}
+
+async fn write_json(socket: &mut ServerSocket, value: Value) {
+    socket
+        .send(WsMessage::text(value.to_string()))
+        .await
+        .unwrap();
+}
+
+async fn live_client(listener: &TcpListener) -> Result<Client> {
+    let addr = listener.local_addr()?;
+    let client = Client::builder()
+        .live_url(&format!("ws://{}/live", addr))
+        .build()?;
+    client.set_api_key("test-key".to_string());
+    Ok(client)
+}
+
+/// Checks that the API key is sent in a header rather than in the URL.
+#[allow(clippy::result_large_err)]
+fn check_api_key(request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+    assert_eq!(request.headers()["x-goog-api-key"], "test-key");
+    assert_eq!(request.uri().query(), None);
+    Ok(response)
+}
+
+#[test]
+fn test_live_build_defaults() {
+    let live = LiveBuilder::default()
+        .model(Model::Flash20Live)
+        .build()
+        .unwrap();
+
+    assert_eq!(live.model, Model::Flash20Live);
+    assert_eq!(live.response_modalities, vec!["TEXT".to_string()]);
+    assert!(live.system.is_none());
+    assert!(live.tools.is_none());
+}
+
+#[tokio::test]
+async fn test_live_text_session() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let client = live_client(&listener).await?;
+
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = accept_hdr_async(stream, check_api_key).await.unwrap();
+
+        let setup = read_json(&mut socket).await;
+        assert_eq!(setup["setup"]["model"], "models/gemini-2.0-flash-live-001");
+        assert_eq!(
+            setup["setup"]["generationConfig"]["responseModalities"],
+            json!(["TEXT"])
+        );
+        write_json(&mut socket, json!({ "setupComplete": {} })).await;
+
+        let content = read_json(&mut socket).await;
+        assert_eq!(
+            content["clientContent"]["turns"][0]["parts"][0]["text"],
+            "Hi"
+        );
+        assert_eq!(content["clientContent"]["turnComplete"], true);
+
+        write_json(
+            &mut socket,
+            json!({ "serverContent": { "modelTurn": { "parts": [{ "text": "Hello" }] } } }),
+        )
+        .await;
+        write_json(
+            &mut socket,
+            json!({ "serverContent": { "modelTurn": { "parts": [{ "text": " there" }] }, "turnComplete": true } }),
+        )
+        .await;
+
+        while let Some(Ok(_)) = socket.next().await {}
+    });
+
+    let params = LiveBuilder::default().model(Model::Flash20Live).build()?;
+    let mut session = client.live().connect(params).await?;
+    session.send_text("Hi").await?;
+
+    let mut events = Vec::new();
+    while let Some(event) = session.receive().await {
+        let event = event?;
+        let done = event == LiveEvent::TurnComplete;
+        events.push(event);
+        if done {
+            break;
+        }
+    }
+
+    assert_eq!(
+        events,
+        vec![
+            LiveEvent::Text("Hello".into()),
+            LiveEvent::Text(" there".into()),
+            LiveEvent::TurnComplete,
+        ]
+    );
+
+    session.close().await?;
+    server.await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_live_audio_tool_call_and_interruption() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let client = live_client(&listener).await?;
+
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = accept_async(stream).await.unwrap();
+
+        let setup = read_json(&mut socket).await;
+        assert_eq!(
+            setup["setup"]["tools"][0]["functionDeclarations"][0]["name"],
+            "get_weather"
+        );
+        write_json(&mut socket, json!({ "setupComplete": {} })).await;
+
+        let audio = read_json(&mut socket).await;
+        assert_eq!(
+            audio["realtimeInput"]["audio"]["mimeType"],
+            "audio/pcm;rate=16000"
+        );
+        assert_eq!(audio["realtimeInput"]["audio"]["data"], "AQID");
+
+        write_json(
+            &mut socket,
+            json!({ "toolCall": { "functionCalls": [{ "id": "call-1", "name": "get_weather", "args": { "city": "Paris" } }] } }),
+        )
+        .await;
+
+        let response = read_json(&mut socket).await;
+        let function_response = &response["toolResponse"]["functionResponses"][0];
+        assert_eq!(function_response["id"], "call-1");
+        assert_eq!(function_response["response"]["forecast"], "sunny");
+
+        write_json(
+            &mut socket,
+            json!({ "serverContent": { "modelTurn": { "parts": [{ "inlineData": { "mimeType": "audio/pcm;rate=24000", "data": "BAUG" } }] } } }),
+        )
+        .await;
+        write_json(
+            &mut socket,
+            json!({ "serverContent": { "interrupted": true } }),
+        )
+        .await;
+        socket.close(None).await.unwrap();
+    });
+
+    let tool = Tool::functions(vec![FunctionDeclaration {
+        name: "get_weather".into(),
+        description: "Returns the weather forecast for a city.".into(),
+        parameters: None,
+    }]);
+    let params = LiveBuilder::default()
+        .model(Model::Flash20Live)
+        .response_modalities(vec!["AUDIO".to_string()])
+        .tools(vec![tool])
+        .build()?;
+
+    let mut session = client.live().connect(params).await?;
+    session
+        .send_audio(&[1, 2, 3], "audio/pcm;rate=16000")
+        .await?;
+
+    let calls = match session.receive().await.unwrap()? {
+        LiveEvent::ToolCall(calls) => calls,
+        other => panic!("Expected a tool call, got {:?}", other),
+    };
+    assert_eq!(calls[0].name, "get_weather");
+    assert_eq!(calls[0].args, Some(json!({ "city": "Paris" })));
+
+    session
+        .send_tool_response(vec![FunctionResponse {
+            id: calls[0].id.clone(),
+            name: calls[0].name.clone(),
+            response: json!({ "forecast": "sunny" }),
+        }])
+        .await?;
+
+    assert_eq!(
+        session.receive().await.unwrap()?,
+        LiveEvent::Audio(vec![4, 5, 6])
+    );
+    assert_eq!(session.receive().await.unwrap()?, LiveEvent::Interrupted);
+    assert!(session.receive().await.is_none());
+
+    server.await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_live_setup_rejected() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let client = live_client(&listener).await?;
+
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = accept_async(stream).await.unwrap();
+        let _ = read_json(&mut socket).await;
+        socket
+            .close(Some(tokio_tungstenite::tungstenite::protocol::CloseFrame {
+                code: 1007.into(),
+                reason: "Unknown model".into(),
+            }))
+            .await
+            .unwrap();
+    });
+
+    let params = LiveBuilder::default().model(Model::Flash20Live).build()?;
+    let result = client.live().connect(params).await;
+
+    assert!(result.is_err());
+    assert!(result.err().unwrap().to_string().contains("Unknown model"));
+
+    server.await?;
+    Ok(())
+}
@@ -0,0 +1,308 @@
+use anyhow::Result;
+use futures_util::TryStreamExt;
+use gems::chat::ChatBuilder;
+use gems::messages::{Content, Message};
+use gems::models::{ModBuilder, Model};
+use gems::traits::CTrait;
+use gems::Client;
+use serde_json::json;
+use std::str::FromStr;
+
+mod common;
+use common::MockServer;
+
+fn model_info(name: &str, methods: &[&str]) -> serde_json::Value {
+    json!({
+        "name": format!("models/{}", name),
+        "version": "001",
+        "displayName": name,
+        "inputTokenLimit": 1024,
+        "outputTokenLimit": 256,
+        "supportedGenerationMethods": methods,
+    })
+}
+
+async fn models_server() -> MockServer {
+    MockServer::start(|request| {
+        let body = if request.path == "/models/gemini-1.5-pro" {
+            model_info("gemini-1.5-pro", &["generateContent"])
+        } else if request.path.contains("pageToken=page-2") {
+            json!({ "models": [model_info("text-embedding-004", &["embedContent"])] })
+        } else {
+            json!({
+                "models": [
+                    model_info("gemini-2.0-flash", &["generateContent", "countTokens"]),
+                    model_info("gemini-1.5-pro", &["generateContent"]),
+                ],
+                "nextPageToken": "page-2",
+            })
+        };
+        (200, body.to_string())
+    })
+    .await
+}
+
+async fn test_client(server: &MockServer) -> Result<Client> {
+    let client = Client::builder().base_url(&server.url).build()?;
+    client.set_api_key("test-key".to_string());
+    Ok(client)
+}
+
+#[test]
+fn test_parse_known_models() {
+    assert_eq!(Model::from_str("gemini-2.0-flash").unwrap(), Model::Flash20);
+    assert_eq!(
+        Model::from_str("models/gemini-1.5-pro").unwrap(),
+        Model::Pro15
+    );
+    assert_eq!(
+        Model::from_str("gemini-2.0-flash-exp-image-generation").unwrap(),
+        Model::FlashExpImage
+    );
+}
+
+#[test]
+fn test_known_models_round_trip() {
+    let models = vec![
+        Model::Pro25Preview,
+        Model::Flash20,
+        Model::Flash20Lite,
+        Model::Flash15,
+        Model::Flash15_8B,
+        Model::Pro15,
+        Model::Embedding,
+        Model::Imagen3,
+        Model::Veo2,
+        Model::Tts,
+        Model::Flash20Live,
+        Model::FlashExpImage,
+    ];
+
+    for model in models {
+        assert_eq!(Model::from_str(&model.to_string()).unwrap(), model);
+    }
+}
+
+#[test]
+fn test_parse_custom_models() {
+    let pinned = Model::from_str("gemini-2.5-flash-preview-05-20").unwrap();
+    assert_eq!(
+        pinned,
+        Model::Custom("gemini-2.5-flash-preview-05-20".into())
+    );
+    assert_eq!(pinned.to_string(), "gemini-2.5-flash-preview-05-20");
+    assert_eq!(
+        pinned.resource_name(),
+        "models/gemini-2.5-flash-preview-05-20"
+    );
+
+    let prefixed = Model::from_str("models/gemini-exp-1206").unwrap();
+    assert_eq!(prefixed, Model::Custom("gemini-exp-1206".into()));
+
+    let tuned = Model::from_str("tunedModels/my-tuned-model-42").unwrap();
+    assert_eq!(tuned.to_string(), "tunedModels/my-tuned-model-42");
+    assert_eq!(tuned.resource_name(), "tunedModels/my-tuned-model-42");
+}
+
+#[test]
+fn test_parse_malformed_models() {
+    for name in [
+        "",
+        "gemini 2.0 flash",
+        "-gemini",
+        "models/",
+        "tunedModels/",
+        "foo/bar",
+        "gemini-2.0-flash?key=x",
+    ] {
+        assert!(
+            Model::from_str(name).is_err(),
+            "{:?} should be rejected",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_builder_rejects_malformed_model() {
+    assert!(Client::builder().model("not a model").build().is_err());
+    assert!(Client::builder().model("gemini-2.5-pro").build().is_ok());
+}
+
+#[tokio::test]
+async fn test_list_follows_pages() -> Result<()> {
+    let server = models_server().await;
+    let client = test_client(&server).await?;
+
+    let models = client.models().list().await?;
+    let names: Vec<&str> = models.models.iter().map(|m| m.name.as_str()).collect();
+
+    assert_eq!(
+        names,
+        vec![
+            "models/gemini-2.0-flash",
+            "models/gemini-1.5-pro",
+            "models/text-embedding-004",
+        ]
+    );
+    assert!(models.next_page_token.is_none());
+    assert_eq!(server.requests().len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stream_is_lazy() -> Result<()> {
+    let server = models_server().await;
+    let client = test_client(&server).await?;
+
+    let models = client.models();
+    let mut stream = Box::pin(models.stream());
+    let first = stream.try_next().await?.unwrap();
+
+    assert_eq!(first.name, "models/gemini-2.0-flash");
+    assert_eq!(server.requests().len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_supporting_filters_by_method() -> Result<()> {
+    let server = models_server().await;
+    let client = test_client(&server).await?;
+
+    let embedders = client.models().supporting("embedContent").await?;
+    assert_eq!(embedders.len(), 1);
+    assert_eq!(embedders[0].name, "models/text-embedding-004");
+
+    let listed = client.models().list().await?;
+    assert_eq!(listed.supporting("countTokens").len(), 1);
+    assert_eq!(listed.supporting("generateContent").len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_uses_requested_model() -> Result<()> {
+    let server = models_server().await;
+    let client = test_client(&server).await?;
+
+    let params = ModBuilder::default().model(Model::Pro15).build()?;
+    let info = client.models().get(params).await?;
+
+    assert_eq!(info.name, "models/gemini-1.5-pro");
+    assert!(info.supports("generateContent"));
+    assert!(!info.supports("embedContent"));
+    assert_eq!(server.requests()[0].path, "/models/gemini-1.5-pro");
+
+    Ok(())
+}
+
+async fn preflight_server() -> MockServer {
+    MockServer::start(|request| {
+        let body = if request.path == "/models/text-embedding-004" {
+            model_info("text-embedding-004", &["embedContent"])
+        } else if request.path == "/models/gemini-2.0-flash" {
+            model_info("gemini-2.0-flash", &["generateContent", "countTokens"])
+        } else if request.path.contains(":countTokens") {
+            json!({ "totalTokens": 2048 })
+        } else {
+            json!({ "candidates": [{ "content": { "parts": [{ "text": "Hi!" }] } }] })
+        };
+        (200, body.to_string())
+    })
+    .await
+}
+
+fn hello() -> Message {
+    Message::User {
+        content: Content::Text("Hello".into()),
+        name: None,
+    }
+}
+
+#[tokio::test]
+async fn test_preflight_rejects_unsupported_method() -> Result<()> {
+    let server = preflight_server().await;
+    let client = Client::builder()
+        .base_url(&server.url)
+        .preflight(true)
+        .build()?;
+    client.set_api_key("test-key".to_string());
+
+    let chat = ChatBuilder::default()
+        .model(Model::Embedding)
+        .messages(vec![hello()])
+        .build()?;
+    let err = client.chat().generate(chat).await.unwrap_err();
+
+    assert!(err
+        .to_string()
+        .contains("text-embedding-004 does not support generateContent"));
+    assert!(server
+        .requests()
+        .iter()
+        .all(|request| !request.path.contains(":generateContent")));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_preflight_rejects_prompt_over_limit() -> Result<()> {
+    let server = preflight_server().await;
+    let client = Client::builder()
+        .base_url(&server.url)
+        .preflight(true)
+        .build()?;
+    client.set_api_key("test-key".to_string());
+
+    let chat = ChatBuilder::default()
+        .model(Model::Flash20)
+        .messages(vec![hello()])
+        .build()?;
+    let err = client.chat().generate(chat).await.unwrap_err();
+
+    assert!(err.to_string().contains("exceeding the input token limit"));
+
+    let count = server
+        .requests()
+        .into_iter()
+        .find(|request| request.path.contains(":countTokens"))
+        .unwrap();
+    assert_eq!(
+        count.json()["generateContentRequest"]["model"],
+        "models/gemini-2.0-flash"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_model_info_is_cached() -> Result<()> {
+    let server = preflight_server().await;
+    let client = test_client(&server).await?;
+
+    let first = client.models().info(&Model::Embedding).await?;
+    let second = client.clone().models().info(&Model::Embedding).await?;
+
+    assert_eq!(first.name, second.name);
+    assert_eq!(server.requests().len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_preflight_disabled_by_default() -> Result<()> {
+    let server = preflight_server().await;
+    let client = test_client(&server).await?;
+
+    let chat = ChatBuilder::default()
+        .model(Model::Flash20)
+        .messages(vec![hello()])
+        .build()?;
+
+    assert_eq!(client.chat().generate(chat).await?, "Hi!");
+    assert_eq!(server.requests().len(), 1);
+
+    Ok(())
+}
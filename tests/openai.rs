@@ -0,0 +1,266 @@
+use anyhow::Result;
+use futures_util::StreamExt;
+use gems::chat::ChatBuilder;
+use gems::client::Transport;
+use gems::embed::{BatchEmbeddingBuilder, EmbeddingBuilder};
+use gems::messages::{Content, Message};
+use gems::requests::{FunctionDeclaration, Tool};
+use gems::traits::CTrait;
+use gems::Client;
+use serde_json::json;
+
+mod common;
+use common::{MockServer, Recorded};
+
+fn openai(request: &Recorded) -> (u16, String) {
+    let body = if request.path == "/v1/embeddings" {
+        r#"{"object": "list", "data": [
+            {"object": "embedding", "index": 1, "embedding": [0.3, 0.4]},
+            {"object": "embedding", "index": 0, "embedding": [0.1, 0.2]}
+        ]}"#
+    } else if request.json()["stream"] == true && request.json()["tools"].is_array() {
+        concat!(
+            "data: {\"choices\": [{\"index\": 0, \"delta\": {\"role\": \"assistant\", \"content\": null, \"tool_calls\": [{\"index\": 0, \"id\": \"call_1\", \"type\": \"function\", \"function\": {\"name\": \"get_weather\", \"arguments\": \"\"}}]}, \"finish_reason\": null}]}\n\n",
+            "data: {\"choices\": [{\"index\": 0, \"delta\": {\"tool_calls\": [{\"index\": 0, \"function\": {\"arguments\": \"{\\\"city\\\":\"}}]}, \"finish_reason\": null}]}\n\n",
+            "data: {\"choices\": [{\"index\": 0, \"delta\": {\"tool_calls\": [{\"index\": 0, \"function\": {\"arguments\": \"\\\"Paris\\\"}\"}}, {\"index\": 1, \"id\": \"call_2\", \"function\": {\"name\": \"get_time\", \"arguments\": \"{}\"}}]}, \"finish_reason\": null}]}\n\n",
+            "data: {\"choices\": [{\"index\": 0, \"delta\": {}, \"finish_reason\": \"tool_calls\"}]}\n\n",
+            "data: [DONE]\n\n",
+        )
+    } else if request.json()["stream"] == true {
+        concat!(
+            "data: {\"choices\": [{\"index\": 0, \"delta\": {\"role\": \"assistant\", \"content\": \"Hel\"}, \"finish_reason\": null}], \"usage\": null}\n\n",
+            "data: {\"choices\": [{\"index\": 0, \"delta\": {\"content\": \"lo\"}, \"finish_reason\": \"stop\"}], \"usage\": null}\n\n",
+            "data: {\"choices\": [], \"usage\": {\"prompt_tokens\": 3, \"completion_tokens\": 2, \"total_tokens\": 5}}\n\n",
+            "data: [DONE]\n\n",
+        )
+    } else if request.json()["tools"].is_array() {
+        r#"{"choices": [{"index": 0, "message": {"role": "assistant", "content": null, "tool_calls": [
+            {"id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}}
+        ]}, "finish_reason": "tool_calls"}]}"#
+    } else {
+        r#"{"id": "chatcmpl-1", "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hello!"}, "finish_reason": "length"}],
+            "usage": {"prompt_tokens": 5, "completion_tokens": 2, "total_tokens": 7}}"#
+    };
+    (200, body.to_string())
+}
+
+fn client(server: &MockServer) -> Result<Client> {
+    let client = Client::builder()
+        .model("local-model")
+        .base_url(&format!("{}/v1", server.url))
+        .transport(Transport::OpenAi)
+        .build()?;
+    client.set_api_key("test-key".to_string());
+    Ok(client)
+}
+
+fn user(text: &str) -> Message {
+    Message::User {
+        content: Content::Text(text.to_string()),
+        name: None,
+    }
+}
+
+#[tokio::test]
+async fn test_chat_completions() -> Result<()> {
+    let server = MockServer::start(openai).await;
+    let client = client(&server)?;
+
+    let params = ChatBuilder::default()
+        .messages(vec![
+            user("Hi"),
+            Message::Model {
+                content: Content::Text("Hello, how can I help?".to_string()),
+            },
+            user("Say hello"),
+        ])
+        .system(vec![Message::System {
+            content: Content::Text("Be brief.".to_string()),
+            name: None,
+        }])
+        .build()?;
+    let response = client.chat().generate_response(params).await?;
+
+    assert_eq!(response.text().as_deref(), Some("Hello!"));
+    let candidate = &response.candidates.as_ref().unwrap()[0];
+    assert_eq!(candidate.finish_reason.as_deref(), Some("MAX_TOKENS"));
+    let usage = response.usage_metadata.unwrap();
+    assert_eq!(usage.prompt_token_count, Some(5));
+    assert_eq!(usage.candidates_token_count, Some(2));
+
+    let recorded = &server.requests()[0];
+    assert_eq!(recorded.path, "/v1/chat/completions");
+    assert_eq!(recorded.header("authorization"), Some("Bearer test-key"));
+    let body = recorded.json();
+    assert_eq!(body["model"], "local-model");
+    assert_eq!(
+        body["messages"],
+        json!([
+            { "role": "system", "content": "Be brief." },
+            { "role": "user", "content": "Hi" },
+            { "role": "assistant", "content": "Hello, how can I help?" },
+            { "role": "user", "content": "Say hello" }
+        ])
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tool_calls() -> Result<()> {
+    let server = MockServer::start(openai).await;
+    let client = client(&server)?;
+
+    let params = ChatBuilder::default()
+        .messages(vec![user("Weather in Paris?")])
+        .tools(vec![Tool::functions(vec![FunctionDeclaration {
+            name: "get_weather".to_string(),
+            description: "Gets the weather of a city.".to_string(),
+            parameters: Some(json!({ "type": "object" })),
+        }])])
+        .build()?;
+    let response = client.chat().generate_response(params).await?;
+
+    let body = server.requests()[0].json();
+    assert_eq!(body["tools"][0]["type"], "function");
+    assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+
+    let parts = &response.candidates.unwrap()[0].content.parts;
+    match &parts[0] {
+        gems::requests::Part::FunctionCall { function_call, .. } => {
+            assert_eq!(function_call.id.as_deref(), Some("call_1"));
+            assert_eq!(function_call.name, "get_weather");
+            assert_eq!(function_call.args, Some(json!({ "city": "Paris" })));
+        }
+        part => panic!("Expected a function call, got {:?}", part),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chat_stream() -> Result<()> {
+    let server = MockServer::start(openai).await;
+    let client = client(&server)?;
+
+    let params = ChatBuilder::default().messages(vec![user("Hi")]).build()?;
+    let chunks: Vec<_> = client.chat().stream(params).await?.collect().await;
+    let chunks = chunks.into_iter().collect::<Result<Vec<_>>>()?;
+
+    let text: String = chunks.iter().filter_map(|chunk| chunk.text()).collect();
+    assert_eq!(text, "Hello");
+    let usage = chunks.last().unwrap().usage_metadata.as_ref().unwrap();
+    assert_eq!(usage.total_token_count, Some(5));
+
+    let body = server.requests()[0].json();
+    assert_eq!(body["stream"], true);
+    assert_eq!(body["stream_options"]["include_usage"], true);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_embeddings() -> Result<()> {
+    let server = MockServer::start(openai).await;
+    let client = client(&server)?;
+
+    let params = BatchEmbeddingBuilder::default()
+        .model(gems::models::Model::Embedding)
+        .input(vec![user("a"), user("b")])
+        .output_dimensionality(2u32)
+        .build()?;
+    let response = client.embeddings().batch(params).await?;
+    let values: Vec<_> = response
+        .embeddings
+        .unwrap()
+        .into_iter()
+        .map(|e| e.values)
+        .collect();
+    assert_eq!(values, vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
+
+    let body = server.requests()[0].json();
+    assert_eq!(
+        body,
+        json!({ "model": "text-embedding-004", "input": ["a", "b"], "dimensions": 2 })
+    );
+
+    let params = EmbeddingBuilder::default().input(user("a")).build()?;
+    let response = client.embeddings().create(params).await?;
+    assert!(response.embedding.is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unsupported_features() -> Result<()> {
+    let server = MockServer::start(openai).await;
+    let client = client(&server)?;
+
+    let params = ChatBuilder::default()
+        .messages(vec![user("Hi")])
+        .tools(vec![Tool::google_search()])
+        .build()?;
+    let error = client.chat().generate(params).await.unwrap_err();
+    assert!(error.to_string().contains("not supported"));
+
+    assert!(client.models().list().await.is_err());
+    assert!(server.requests().is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chat_stream_tool_calls() -> Result<()> {
+    let server = MockServer::start(openai).await;
+    let client = client(&server)?;
+
+    let params = ChatBuilder::default()
+        .messages(vec![user("Weather and time in Paris?")])
+        .tools(vec![Tool::functions(vec![FunctionDeclaration {
+            name: "get_weather".to_string(),
+            description: "Gets the weather of a city.".to_string(),
+            parameters: Some(json!({ "type": "object" })),
+        }])])
+        .build()?;
+    let chunks: Vec<_> = client.chat().stream(params).await?.collect().await;
+    let chunks = chunks.into_iter().collect::<Result<Vec<_>>>()?;
+
+    let calls: Vec<_> = chunks
+        .iter()
+        .flat_map(|chunk| chunk.function_calls().into_iter().cloned())
+        .collect();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].id.as_deref(), Some("call_1"));
+    assert_eq!(calls[0].name, "get_weather");
+    assert_eq!(calls[0].args, Some(json!({ "city": "Paris" })));
+    assert_eq!(calls[1].id.as_deref(), Some("call_2"));
+    assert_eq!(calls[1].name, "get_time");
+    assert_eq!(calls[1].args, Some(json!({})));
+
+    let last = &chunks.last().unwrap().candidates.as_ref().unwrap()[0];
+    assert_eq!(last.finish_reason.as_deref(), Some("STOP"));
+    assert_eq!(last.content.parts.len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_credentials() -> Result<()> {
+    let server = MockServer::start(openai).await;
+    let client = Client::builder()
+        .model("local-model")
+        .base_url(&format!("{}/v1", server.url))
+        .transport(Transport::OpenAi)
+        .credentials(gems::auth::StaticToken("oauth-token".to_string()))
+        .build()?;
+    client.set_api_key("ignored-key".to_string());
+
+    let params = ChatBuilder::default().messages(vec![user("Hi")]).build()?;
+    client.chat().generate(params).await?;
+
+    let recorded = &server.requests()[0];
+    assert_eq!(recorded.header("authorization"), Some("Bearer oauth-token"));
+    assert_eq!(
+        recorded
+            .headers
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case("authorization"))
+            .count(),
+        1
+    );
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use gems::requests::{Content, FunctionCall, Part};
+use gems::responses::GeminiResponse;
+use serde_json::json;
+
+#[test]
+fn test_parts_round_trip() {
+    let content = json!({
+        "role": "model",
+        "parts": [
+            { "text": "Planning the answer.", "thought": true },
+            { "text": "Here is the chart." },
+            { "inlineData": { "mimeType": "image/png", "data": "iVBORw0KGgo=" } },
+            {
+                "fileData": { "mimeType": "video/mp4", "fileUri": "https://example.com/clip.mp4" },
+                "videoMetadata": { "startOffset": "1s", "endOffset": "5s", "fps": 2.0 }
+            },
+            { "functionCall": { "id": "call-1", "name": "lookup", "args": { "q": "rust" } } },
+            { "functionResponse": { "id": "call-1", "name": "lookup", "response": { "hits": 3 } } },
+            { "executableCode": { "language": "PYTHON", "code": "print(1)" } },
+            { "codeExecutionResult": { "outcome": "OUTCOME_OK", "output": "1\n" } }
+        ]
+    });
+
+    let parsed: Content = serde_json::from_value(content.clone()).unwrap();
+    assert!(matches!(parsed.parts[0], Part::Thought { .. }));
+    assert!(matches!(parsed.parts[2], Part::InlineData { .. }));
+    assert!(matches!(
+        &parsed.parts[3],
+        Part::FileData { video_metadata: Some(metadata), .. } if metadata.fps == Some(2.0)
+    ));
+    assert!(matches!(parsed.parts[4], Part::FunctionCall { .. }));
+    assert!(matches!(parsed.parts[5], Part::FunctionResponse { .. }));
+    assert!(matches!(parsed.parts[7], Part::CodeExecutionResult { .. }));
+
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), content);
+}
+
+#[test]
+fn test_thought_signatures_round_trip() {
+    let content = json!({
+        "role": "model",
+        "parts": [
+            { "text": "Thinking.", "thought": true, "thoughtSignature": "c2lnLTA=" },
+            { "text": "Let me check.", "thoughtSignature": "c2lnLTE=" },
+            {
+                "functionCall": { "id": "call-1", "name": "lookup", "args": { "q": "rust" } },
+                "thoughtSignature": "c2lnLTI="
+            }
+        ]
+    });
+
+    let parsed: Content = serde_json::from_value(content.clone()).unwrap();
+    assert!(matches!(
+        &parsed.parts[1],
+        Part::Text { thought_signature: Some(signature), .. } if signature == "c2lnLTE="
+    ));
+    assert!(matches!(
+        &parsed.parts[2],
+        Part::FunctionCall { thought_signature: Some(signature), .. } if signature == "c2lnLTI="
+    ));
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), content);
+
+    let part: Part = serde_json::from_value(json!({
+        "text": "Hi", "thought_signature": "c2ln"
+    }))
+    .unwrap();
+    assert_eq!(
+        serde_json::to_value(&part).unwrap(),
+        json!({ "text": "Hi", "thoughtSignature": "c2ln" })
+    );
+}
+
+#[test]
+fn test_parts_accept_snake_case() {
+    let part: Part = serde_json::from_value(json!({
+        "inline_data": { "mime_type": "audio/pcm", "data": "AAEC" }
+    }))
+    .unwrap();
+    assert_eq!(part, Part::inline_data("audio/pcm", "AAEC"));
+    assert_eq!(
+        serde_json::to_value(&part).unwrap(),
+        json!({ "inlineData": { "mimeType": "audio/pcm", "data": "AAEC" } })
+    );
+}
+
+#[test]
+fn test_thought_false_is_text() {
+    let part: Part = serde_json::from_value(json!({ "text": "Hi", "thought": false })).unwrap();
+    assert_eq!(part, Part::text("Hi"));
+}
+
+#[test]
+fn test_function_call_skips_missing_fields() {
+    let part = Part::FunctionCall {
+        function_call: FunctionCall {
+            id: None,
+            name: "now".to_string(),
+            args: None,
+        },
+        thought_signature: None,
+    };
+    assert_eq!(
+        serde_json::to_value(&part).unwrap(),
+        json!({ "functionCall": { "name": "now" } })
+    );
+    assert_eq!(
+        serde_json::from_value::<Part>(json!({ "functionCall": { "name": "now" } })).unwrap(),
+        part
+    );
+}
+
+#[test]
+fn test_candidate_without_content() {
+    let response: GeminiResponse = serde_json::from_value(json!({
+        "candidates": [{ "finishReason": "SAFETY" }]
+    }))
+    .unwrap();
+    let candidate = &response.candidates.as_ref().unwrap()[0];
+    assert_eq!(candidate.finish_reason.as_deref(), Some("SAFETY"));
+    assert!(candidate.content.parts.is_empty());
+    assert!(response.text().is_none());
+}
+
+#[test]
+fn test_unknown_parts_and_fields_are_preserved() {
+    let body = json!({
+        "candidates": [{
+            "content": {
+                "role": "model",
+                "parts": [
+                    { "text": "Done." },
+                    { "toolResult": { "name": "future_tool", "ok": true } }
+                ]
+            },
+            "finishReason": "STOP",
+            "avgLogprobs": -0.25
+        }],
+        "usageMetadata": { "promptTokenCount": 3, "toolUsePromptTokenCount": 7 },
+        "modelVersion": "gemini-next",
+        "responseId": "abc"
+    });
+
+    let response: GeminiResponse = serde_json::from_value(body.clone()).unwrap();
+    assert_eq!(response.text().as_deref(), Some("Done."));
+    assert_eq!(response.extra["modelVersion"], "gemini-next");
+    assert_eq!(response.extra["responseId"], "abc");
+
+    let candidate = &response.candidates.as_ref().unwrap()[0];
+    assert_eq!(candidate.extra["avgLogprobs"], -0.25);
+    assert_eq!(
+        candidate.content.parts[1],
+        Part::Unknown(json!({ "toolResult": { "name": "future_tool", "ok": true } }))
+    );
+    assert_eq!(
+        serde_json::to_value(candidate).unwrap(),
+        body["candidates"][0]
+    );
+
+    let usage = response.usage_metadata.unwrap();
+    assert_eq!(usage.prompt_token_count, Some(3));
+    assert_eq!(usage.extra["toolUsePromptTokenCount"], 7);
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use anyhow::Result;
+use common::MockServer;
+use gems::rag::{cited_ids, AskBuilder, ChunkUnit, ChunkerBuilder, Corpus, Document};
+use gems::traits::CTrait;
+use gems::Client;
+use serde_json::{json, Value};
+
+fn keyword_embedding(text: &str) -> Value {
+    let text = text.to_lowercase();
+    let rust = if text.contains("rust") { 1.0 } else { 0.0 };
+    let python = if text.contains("python") { 1.0 } else { 0.0 };
+    json!({ "values": [rust, python] })
+}
+
+#[test]
+fn test_chunk_characters_with_overlap() {
+    let chunker = ChunkerBuilder::default()
+        .size(4usize)
+        .overlap(2usize)
+        .build()
+        .unwrap();
+    let chunks = chunker.chunk(&Document {
+        id: "doc".into(),
+        text: "abcdefghij".into(),
+    });
+
+    let texts: Vec<&str> = chunks.iter().map(|c| c.text.as_str()).collect();
+    assert_eq!(texts, vec!["abcd", "cdef", "efgh", "ghij"]);
+    assert_eq!(chunks[1].id, "doc#1");
+    assert_eq!(chunks[1].document, "doc");
+}
+
+#[test]
+fn test_chunk_tokens() {
+    let chunker = ChunkerBuilder::default()
+        .size(3usize)
+        .overlap(1usize)
+        .unit(ChunkUnit::Tokens)
+        .build()
+        .unwrap();
+    let chunks = chunker.chunk(&Document {
+        id: "doc".into(),
+        text: "one two  three\nfour five".into(),
+    });
+
+    let texts: Vec<&str> = chunks.iter().map(|c| c.text.as_str()).collect();
+    assert_eq!(texts, vec!["one two three", "three four five"]);
+}
+
+#[test]
+fn test_corpus_from_dir() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("gems-corpus-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("guides"))?;
+    std::fs::write(dir.join("intro.md"), "Hello")?;
+    std::fs::write(dir.join("guides/setup.txt"), "Setup")?;
+    std::fs::write(dir.join("logo.png"), [0u8, 1, 2])?;
+
+    let corpus = Corpus::from_dir(&dir)?;
+    std::fs::remove_dir_all(&dir)?;
+
+    let ids: Vec<&str> = corpus.documents.iter().map(|d| d.id.as_str()).collect();
+    assert_eq!(ids, vec!["guides/setup.txt", "intro.md"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_index_and_ask() -> Result<()> {
+    let server = MockServer::start(|request| {
+        let json = request.json();
+        if request.path.contains(":batchEmbedContents") {
+            let embeddings: Vec<Value> = json["requests"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|r| keyword_embedding(r["content"]["parts"][0]["text"].as_str().unwrap()))
+                .collect();
+            (200, json!({ "embeddings": embeddings }).to_string())
+        } else if request.path.contains(":embedContent") {
+            let text = json["content"]["parts"][0]["text"].as_str().unwrap();
+            (200, json!({ "embedding": keyword_embedding(text) }).to_string())
+        } else {
+            (
+                200,
+                json!({ "candidates": [{ "content": { "role": "model", "parts": [{ "text": "Use cargo [rust.md#0]." }] } }] })
+                    .to_string(),
+            )
+        }
+    })
+    .await;
+    let gemini_client = Client::builder().base_url(&server.url).build()?;
+    gemini_client.set_api_key("test-key".to_string());
+
+    let corpus = Corpus::new(vec![
+        Document {
+            id: "rust.md".into(),
+            text: "Rust projects are built with cargo.".into(),
+        },
+        Document {
+            id: "python.md".into(),
+            text: "Python projects use pip.".into(),
+        },
+    ]);
+    let chunks = corpus.chunks(&Default::default());
+    let index = gemini_client.rag().index(&chunks, None, None).await?;
+    assert_eq!(index.len(), 2);
+
+    let params = AskBuilder::default()
+        .question("How do I build a Rust project?")
+        .top_k(1usize)
+        .build()?;
+    let answer = gemini_client.rag().ask(&index, params).await?;
+
+    assert_eq!(answer.text, "Use cargo [rust.md#0].");
+    assert_eq!(answer.sources.len(), 1);
+    assert_eq!(answer.sources[0].id, "rust.md#0");
+    assert_eq!(answer.citations, vec!["rust.md#0".to_string()]);
+
+    let requests = server.requests();
+    assert_eq!(
+        requests[0].json()["requests"][0]["taskType"],
+        "RETRIEVAL_DOCUMENT"
+    );
+    assert_eq!(requests[1].json()["taskType"], "RETRIEVAL_QUERY");
+    let prompt = requests[2].json()["contents"][0]["parts"][0]["text"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(prompt.contains("[rust.md#0]\nRust projects are built with cargo."));
+    assert!(!prompt.contains("pip"));
+    Ok(())
+}
+
+#[test]
+fn test_cited_ids() {
+    let source = |id: &str| gems::embed::index::SearchResult {
+        id: id.into(),
+        score: 1.0,
+        metadata: Value::Null,
+    };
+    let sources = vec![source("a#0"), source("a#1"), source("b#0")];
+
+    assert_eq!(
+        cited_ids("See [b#0, a#0] and [x].", &sources),
+        vec!["a#0".to_string(), "b#0".to_string()]
+    );
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use anyhow::Result;
+use common::MockServer;
+use gems::chat::ChatBuilder;
+use gems::messages::{Content, Message};
+use gems::models::Model;
+use gems::ratelimit::{RateLimiter, RateLimits};
+use gems::traits::CTrait;
+use gems::Client;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[tokio::test(start_paused = true)]
+async fn test_requests_per_minute() -> Result<()> {
+    let limiter = RateLimiter::new().limit(Model::Flash20, RateLimits::new().rpm(2));
+    let start = Instant::now();
+
+    limiter.acquire(&Model::Flash20, 0).await?;
+    limiter.acquire(&Model::Flash20, 0).await?;
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    limiter.acquire(&Model::Flash20, 0).await?;
+    assert_eq!(start.elapsed(), Duration::from_secs(60));
+
+    // Other models are not limited.
+    limiter.acquire(&Model::Flash15, 0).await?;
+    assert_eq!(start.elapsed(), Duration::from_secs(60));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tokens_per_minute() -> Result<()> {
+    let limiter = RateLimiter::new().default_limits(RateLimits::new().tpm(100));
+    let start = Instant::now();
+
+    limiter.acquire(&Model::Flash20, 60).await?;
+    tokio::time::advance(Duration::from_secs(10)).await;
+    limiter.acquire(&Model::Flash20, 30).await?;
+    limiter.acquire(&Model::Flash20, 50).await?;
+    assert_eq!(start.elapsed(), Duration::from_secs(60));
+
+    let err = limiter.acquire(&Model::Flash20, 101).await.unwrap_err();
+    assert!(err.to_string().contains("exceeds the limit"));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_requests_per_day() -> Result<()> {
+    let limiter = RateLimiter::new().limit(Model::Pro15, RateLimits::new().rpm(10).rpd(1));
+    let start = Instant::now();
+
+    limiter.acquire(&Model::Pro15, 0).await?;
+    limiter.acquire(&Model::Pro15, 0).await?;
+    assert_eq!(start.elapsed(), Duration::from_secs(24 * 60 * 60));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_zero_request_limits() -> Result<()> {
+    let limiter = RateLimiter::new()
+        .limit(Model::Flash20, RateLimits::new().rpm(0))
+        .limit(Model::Pro15, RateLimits::new().rpd(0));
+
+    let err = limiter.acquire(&Model::Flash20, 0).await.unwrap_err();
+    assert!(err.to_string().contains("at least one request"));
+    assert!(limiter.acquire(&Model::Pro15, 0).await.is_err());
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_limiter_shared_across_client_clones() -> Result<()> {
+    let server = MockServer::start(|_| {
+        (
+            200,
+            r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "Hi"}]}}]}"#
+                .to_string(),
+        )
+    })
+    .await;
+    let gemini_client = Client::builder()
+        .base_url(&server.url)
+        .rate_limiter(RateLimiter::new().default_limits(RateLimits::new().rpm(1)))
+        .build()?;
+    gemini_client.set_api_key("test-key".to_string());
+
+    let params = ChatBuilder::default()
+        .messages(vec![Message::User {
+            content: Content::Text("Hello".into()),
+            name: None,
+        }])
+        .build()?;
+
+    let chats = gemini_client.chat();
+    let other_chats = gemini_client.clone().chat();
+    let start = Instant::now();
+    let (first, second) =
+        tokio::join!(chats.generate(params.clone()), other_chats.generate(params));
+    first?;
+    second?;
+
+    assert!(start.elapsed() >= Duration::from_secs(60));
+    assert_eq!(server.requests().len(), 2);
+    Ok(())
+}
@@ -0,0 +1,311 @@
+#![cfg(feature = "server")]
+
+use anyhow::Result;
+use gems::traits::CTrait;
+use gems::Client;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+
+mod common;
+use common::{gemini, MockServer, Recorded};
+
+/// Answers like `common::gemini`, with a function call to the first turn of requests
+/// declaring tools.
+fn gemini_with_tools(request: &Recorded) -> (u16, String) {
+    let wants_call = !request.body.is_empty()
+        && request.json()["tools"].is_array()
+        && request.json()["contents"].as_array().unwrap().len() == 1;
+    if !wants_call {
+        return gemini(request);
+    }
+    let body = if request.path.contains(":streamGenerateContent") {
+        "data: {\"candidates\": [{\"content\": {\"parts\": [{\"functionCall\": {\"name\": \"get_weather\", \"args\": {\"city\": \"Paris\"}}}], \"role\": \"model\"}, \"finishReason\": \"STOP\"}]}\r\n\r\n"
+    } else {
+        r#"{"candidates": [{"content": {"parts": [{"functionCall": {"name": "get_weather", "args": {"city": "Paris"}}}], "role": "model"}, "finishReason": "STOP"}]}"#
+    };
+    (200, body.to_string())
+}
+
+async fn start_proxy(backend: &MockServer) -> Result<String> {
+    let client = Client::builder().base_url(&backend.url).build()?;
+    client.set_api_key("test-key".to_string());
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}", listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, gems::server::router(client)).await });
+    Ok(url)
+}
+
+#[tokio::test]
+async fn test_chat_completions() -> Result<()> {
+    let backend = MockServer::start(gemini).await;
+    let proxy = start_proxy(&backend).await?;
+
+    let response: Value = reqwest::Client::new()
+        .post(format!("{}/v1/chat/completions", proxy))
+        .json(&json!({
+            "model": "gemini-1.5-flash",
+            "messages": [
+                { "role": "system", "content": "Be brief." },
+                { "role": "user", "content": "Hi" },
+                { "role": "assistant", "content": "Hello, how can I help?" },
+                { "role": "user", "content": [{ "type": "text", "text": "Say hello" }] }
+            ]
+        }))
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    assert_eq!(response["object"], "chat.completion");
+    assert_eq!(response["model"], "gemini-1.5-flash");
+    assert_eq!(response["choices"][0]["message"]["content"], "Hello!");
+    assert_eq!(response["choices"][0]["finish_reason"], "length");
+    assert_eq!(response["usage"]["total_tokens"], 7);
+
+    let recorded = &backend.requests()[0];
+    assert!(recorded
+        .path
+        .contains("models/gemini-1.5-flash:generateContent"));
+    let body = recorded.json();
+    assert_eq!(body["system_instruction"]["parts"][0]["text"], "Be brief.");
+    let roles: Vec<&str> = body["contents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["role"].as_str().unwrap())
+        .collect();
+    assert_eq!(roles, vec!["user", "model", "user"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chat_completions_stream() -> Result<()> {
+    let backend = MockServer::start(gemini).await;
+    let proxy = start_proxy(&backend).await?;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/v1/chat/completions", proxy))
+        .json(&json!({ "messages": [{ "role": "user", "content": "Hi" }], "stream": true }))
+        .send()
+        .await?;
+    assert!(response.headers()["content-type"]
+        .to_str()?
+        .starts_with("text/event-stream"));
+
+    let body = response.text().await?;
+    let events: Vec<&str> = body
+        .lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .collect();
+    assert_eq!(events.last(), Some(&"[DONE]"));
+
+    let chunks: Vec<Value> = events[..events.len() - 1]
+        .iter()
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect();
+    assert_eq!(chunks[0]["choices"][0]["delta"]["role"], "assistant");
+    let text: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(text, "Hello");
+    assert_eq!(
+        chunks.last().unwrap()["choices"][0]["finish_reason"],
+        "stop"
+    );
+    assert!(backend.requests()[0].path.contains("alt=sse"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_embeddings_and_models() -> Result<()> {
+    let backend = MockServer::start(gemini).await;
+    let proxy = start_proxy(&backend).await?;
+    let http = reqwest::Client::new();
+
+    let embeddings: Value = http
+        .post(format!("{}/v1/embeddings", proxy))
+        .json(&json!({ "model": "text-embedding-004", "input": ["a", "b"], "dimensions": 2 }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(embeddings["data"][1]["index"], 1);
+    assert_eq!(embeddings["data"][1]["embedding"], json!([0.3, 0.4]));
+    let body = backend.requests()[0].json();
+    assert_eq!(body["requests"][0]["outputDimensionality"], 2);
+
+    let models: Value = http
+        .get(format!("{}/v1/models", proxy))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(models["data"][0]["id"], "gemini-2.0-flash");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invalid_request() -> Result<()> {
+    let backend = MockServer::start(gemini).await;
+    let proxy = start_proxy(&backend).await?;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/v1/chat/completions", proxy))
+        .json(&json!({ "messages": [{ "role": "critic", "content": "Hi" }] }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), 400);
+    let body: Value = response.json().await?;
+    assert_eq!(body["error"]["type"], "invalid_request_error");
+    assert!(backend.requests().is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chat_completions_vision() -> Result<()> {
+    let backend = MockServer::start(gemini).await;
+    let proxy = start_proxy(&backend).await?;
+
+    reqwest::Client::new()
+        .post(format!("{}/v1/chat/completions", proxy))
+        .json(&json!({
+            "messages": [{ "role": "user", "content": [
+                { "type": "text", "text": "What is this?" },
+                { "type": "image_url", "image_url": { "url": "data:image/png;base64,iVBORw0KGgo=" } }
+            ] }]
+        }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let body = backend.requests()[0].json();
+    assert_eq!(
+        body["contents"],
+        json!([{ "role": "user", "parts": [
+            { "text": "What is this?" },
+            { "inlineData": { "mimeType": "image/png", "data": "iVBORw0KGgo=" } }
+        ] }])
+    );
+    Ok(())
+}
+
+fn weather_tool() -> Value {
+    json!([{ "type": "function", "function": {
+        "name": "get_weather",
+        "description": "Gets the weather of a city.",
+        "parameters": { "type": "object", "properties": { "city": { "type": "string" } } }
+    } }])
+}
+
+#[tokio::test]
+async fn test_chat_completions_tool_calls() -> Result<()> {
+    let backend = MockServer::start(gemini_with_tools).await;
+    let proxy = start_proxy(&backend).await?;
+    let http = reqwest::Client::new();
+    let question = json!({ "role": "user", "content": "Weather in Paris?" });
+
+    let response: Value = http
+        .post(format!("{}/v1/chat/completions", proxy))
+        .json(&json!({ "messages": [question], "tools": weather_tool() }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    let choice = &response["choices"][0];
+    assert_eq!(choice["finish_reason"], "tool_calls");
+    assert_eq!(choice["message"]["content"], Value::Null);
+    let call = &choice["message"]["tool_calls"][0];
+    assert_eq!(call["id"], "call_0");
+    assert_eq!(call["type"], "function");
+    assert_eq!(call["function"]["name"], "get_weather");
+    let arguments: Value = serde_json::from_str(call["function"]["arguments"].as_str().unwrap())?;
+    assert_eq!(arguments, json!({ "city": "Paris" }));
+
+    let response: Value = http
+        .post(format!("{}/v1/chat/completions", proxy))
+        .json(&json!({
+            "messages": [
+                question,
+                choice["message"],
+                { "role": "tool", "tool_call_id": "call_0", "content": "{\"temperature\": 21}" }
+            ],
+            "tools": weather_tool()
+        }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(response["choices"][0]["message"]["content"], "Hello!");
+
+    let requests = backend.requests();
+    let first = requests[0].json();
+    assert_eq!(
+        first["tools"][0]["functionDeclarations"][0]["name"],
+        "get_weather"
+    );
+    let second = requests[1].json();
+    assert_eq!(
+        second["contents"][1],
+        json!({ "role": "model", "parts": [{ "functionCall": {
+            "id": "call_0", "name": "get_weather", "args": { "city": "Paris" }
+        } }] })
+    );
+    assert_eq!(
+        second["contents"][2],
+        json!({ "role": "user", "parts": [{ "functionResponse": {
+            "id": "call_0", "name": "get_weather", "response": { "temperature": 21 }
+        } }] })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chat_completions_stream_tool_calls() -> Result<()> {
+    let backend = MockServer::start(gemini_with_tools).await;
+    let proxy = start_proxy(&backend).await?;
+
+    let body = reqwest::Client::new()
+        .post(format!("{}/v1/chat/completions", proxy))
+        .json(&json!({
+            "messages": [{ "role": "user", "content": "Weather in Paris?" }],
+            "tools": weather_tool(),
+            "stream": true
+        }))
+        .send()
+        .await?
+        .text()
+        .await?;
+    let chunks: Vec<Value> = body
+        .lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .filter(|data| *data != "[DONE]")
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect();
+
+    let choice = &chunks.last().unwrap()["choices"][0];
+    assert_eq!(choice["finish_reason"], "tool_calls");
+    let call = &choice["delta"]["tool_calls"][0];
+    assert_eq!(call["index"], 0);
+    assert_eq!(call["function"]["name"], "get_weather");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_tool_call_id() -> Result<()> {
+    let backend = MockServer::start(gemini).await;
+    let proxy = start_proxy(&backend).await?;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/v1/chat/completions", proxy))
+        .json(&json!({ "messages": [
+            { "role": "user", "content": "Weather in Paris?" },
+            { "role": "tool", "tool_call_id": "call_9", "content": "21" }
+        ] }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), 400);
+    assert!(backend.requests().is_empty());
+    Ok(())
+}
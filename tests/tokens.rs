@@ -0,0 +1,141 @@
+mod common;
+
+use anyhow::Result;
+use common::MockServer;
+use gems::chat::ChatBuilder;
+use gems::messages::{Content, Message};
+use gems::models::Model;
+use gems::requests::{FunctionDeclaration, Tool};
+use gems::tokens::TokenBuilder;
+use gems::traits::CTrait;
+use gems::Client;
+use serde_json::json;
+
+async fn counting_client(status: u16, body: &'static str) -> Result<(MockServer, Client)> {
+    let server = MockServer::start(move |_| (status, body.to_string())).await;
+    let gemini_client = Client::builder().base_url(&server.url).build()?;
+    gemini_client.set_api_key("test-key".to_string());
+    Ok((server, gemini_client))
+}
+
+#[tokio::test]
+async fn test_count_returns_breakdown() -> Result<()> {
+    let (server, gemini_client) = counting_client(
+        200,
+        r#"{"totalTokens": 270, "cachedContentTokenCount": 12, "promptTokensDetails": [{"modality": "TEXT", "tokenCount": 12}, {"modality": "IMAGE", "tokenCount": 258}]}"#,
+    )
+    .await?;
+
+    let params = TokenBuilder::default()
+        .input(Message::User {
+            content: Content::Text("Hello".into()),
+            name: None,
+        })
+        .build()?;
+    let count = gemini_client.tokens().count(params).await?;
+
+    assert_eq!(count.total_tokens, 270);
+    assert_eq!(count.cached_content_token_count, Some(12));
+    assert_eq!(count.prompt_tokens_details.len(), 2);
+    assert_eq!(
+        count.prompt_tokens_details[1].modality.as_deref(),
+        Some("IMAGE")
+    );
+
+    let body = server.requests()[0].json();
+    let request = &body["generateContentRequest"];
+    assert_eq!(request["model"], "models/gemini-2.0-flash");
+    assert_eq!(request["contents"][0]["parts"][0]["text"], "Hello");
+    assert!(request.get("system_instruction").is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_count_reports_api_errors() -> Result<()> {
+    let (_server, gemini_client) = counting_client(
+        400,
+        r#"{"error": {"code": 400, "message": "API key not valid", "status": "INVALID_ARGUMENT"}}"#,
+    )
+    .await?;
+
+    let params = TokenBuilder::default()
+        .input(Message::User {
+            content: Content::Text("Hello".into()),
+            name: None,
+        })
+        .build()?;
+    let err = gemini_client.tokens().count(params).await.unwrap_err();
+
+    assert!(err.to_string().contains("API key not valid"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_count_chat_sends_whole_conversation() -> Result<()> {
+    let (server, gemini_client) = counting_client(200, r#"{"totalTokens": 42}"#).await?;
+
+    let chat = ChatBuilder::default()
+        .model(Model::Flash15)
+        .messages(vec![
+            Message::User {
+                content: Content::Text("What is in this picture?".into()),
+                name: None,
+            },
+            Message::User {
+                content: Content::Image {
+                    mime_type: "image/png".into(),
+                    data: "iVBORw0KGgo=".into(),
+                },
+                name: None,
+            },
+            Message::Model {
+                content: Content::Text("A cat.".into()),
+            },
+            Message::User {
+                content: Content::Text("What color is it?".into()),
+                name: None,
+            },
+        ])
+        .system(vec![Message::System {
+            content: Content::Text("Be brief.".into()),
+            name: None,
+        }])
+        .tools(vec![Tool::functions(vec![FunctionDeclaration {
+            name: "lookup".into(),
+            description: "Looks things up.".into(),
+            parameters: None,
+        }])])
+        .cached_content("cachedContents/abc")
+        .build()?;
+    let count = gemini_client.tokens().count_chat(&chat).await?;
+    assert_eq!(count.total_tokens, 42);
+
+    let recorded = &server.requests()[0];
+    assert!(recorded
+        .path
+        .contains("models/gemini-1.5-flash:countTokens"));
+
+    let body = recorded.json();
+    let request = &body["generateContentRequest"];
+    let roles: Vec<&str> = request["contents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["role"].as_str().unwrap())
+        .collect();
+    assert_eq!(roles, vec!["user", "model", "user"]);
+    assert_eq!(
+        request["contents"][0]["parts"][1]["inlineData"],
+        json!({ "mimeType": "image/png", "data": "iVBORw0KGgo=" })
+    );
+    assert_eq!(
+        request["system_instruction"]["parts"][0]["text"],
+        "Be brief."
+    );
+    assert_eq!(
+        request["tools"][0]["functionDeclarations"][0]["name"],
+        "lookup"
+    );
+    assert_eq!(request["cachedContent"], "cachedContents/abc");
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use futures_util::TryStreamExt;
+use gems::messages::{Content, Message};
+use gems::traits::CTrait;
+use gems::tts::TtsGenBuilder;
+use gems::utils::sse_events;
+use gems::Client;
+
+mod common;
+use common::MockServer;
+
+/// Three events with CRLF line endings, a keep-alive comment, a `data:` field split over
+/// two lines and a last event without a trailing blank line.
+const EVENTS: &str = concat!(
+    ": keep-alive\r\n\r\n",
+    "data: {\"chunk\": 1}\r\n\r\n",
+    "data: {\"chunk\":\r\n",
+    "data: 2}\r\n\r\n",
+    "data: {\"chunk\": 3}",
+);
+
+#[tokio::test]
+async fn test_sse_events() -> Result<()> {
+    let server = MockServer::start_chunked(7, |_| (200, EVENTS.to_string())).await;
+    let res = reqwest::get(&server.url).await?;
+
+    let events: Vec<String> = sse_events(res).try_collect().await?;
+    assert_eq!(
+        events,
+        vec!["{\"chunk\": 1}", "{\"chunk\":\n2}", "{\"chunk\": 3}"]
+    );
+    Ok(())
+}
+
+/// Three audio chunks of `[0, 1, 2]`, `[3, 4, 5]` and `[6, 7]`, with CRLF line endings, a
+/// keep-alive comment, a `data:` field split over two lines and a last event without a
+/// trailing blank line.
+const AUDIO_EVENTS: &str = concat!(
+    ": keep-alive\r\n\r\n",
+    "data: {\"candidates\": [{\"content\": {\"parts\": [{\"inlineData\": {\"mimeType\": \"audio/pcm\", \"data\": \"AAEC\"}}], \"role\": \"model\"}}]}\r\n\r\n",
+    "data: {\"candidates\": [{\"content\": {\"parts\": [{\"inlineData\":\r\n",
+    "data: {\"mimeType\": \"audio/pcm\", \"data\": \"AwQF\"}}], \"role\": \"model\"}}]}\r\n\r\n",
+    "data: {\"candidates\": [{\"content\": {\"parts\": [{\"inlineData\": {\"mimeType\": \"audio/pcm\", \"data\": \"Bgc=\"}}], \"role\": \"model\"}, \"finishReason\": \"STOP\"}]}",
+);
+
+#[tokio::test]
+async fn test_tts_stream_events() -> Result<()> {
+    let server = MockServer::start_chunked(7, |_| (200, AUDIO_EVENTS.to_string())).await;
+    let client = Client::builder()
+        .model("gemini-2.5-flash-preview-tts")
+        .base_url(&server.url)
+        .build()?;
+    client.set_api_key("test-key".to_string());
+
+    let params = TtsGenBuilder::default()
+        .input(Message::User {
+            content: Content::Text("Say hi".to_string()),
+            name: None,
+        })
+        .build()?;
+    let chunks: Vec<Vec<u8>> = client.tts().stream(params).await?.try_collect().await?;
+    assert_eq!(chunks, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6, 7]]);
+
+    let recorded = &server.requests()[0];
+    assert!(recorded.path.contains(":streamGenerateContent"));
+    assert!(recorded.path.contains("alt=sse"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tts_stream_invalid_audio() -> Result<()> {
+    let server = MockServer::start(|_| {
+        (
+            200,
+            "data: {\"candidates\": [{\"content\": {\"parts\": [{\"inlineData\": {\"mimeType\": \"audio/pcm\", \"data\": \"not base64!\"}}]}}]}\n\n".to_string(),
+        )
+    })
+    .await;
+    let client = Client::builder().base_url(&server.url).build()?;
+    client.set_api_key("test-key".to_string());
+
+    let params = TtsGenBuilder::default()
+        .input(Message::User {
+            content: Content::Text("Say hi".to_string()),
+            name: None,
+        })
+        .build()?;
+    let error = client
+        .tts()
+        .stream(params)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("Failed to decode audio"));
+    Ok(())
+}